@@ -0,0 +1,80 @@
+use lexer::CommonJSModuleLexer;
+use std::time::{Duration, Instant};
+
+// run with `cargo bench -p lexer`, the number of modules of the bundle can be passed as an argument
+fn main() {
+  let modules = std::env::args()
+    .skip(1)
+    .find_map(|arg| arg.parse().ok())
+    .unwrap_or(4000);
+  let source = generate_umd_bundle(modules);
+  let start = Instant::now();
+  let lexer = CommonJSModuleLexer::init("bundle.js", &source).expect("could not parse the bundle");
+  let parse = start.elapsed();
+  let mut times = vec![];
+  let mut exports = 0;
+  for _ in 0..5 {
+    let start = Instant::now();
+    exports = lexer.analyze("production", false).exports.len();
+    times.push(start.elapsed());
+  }
+  times.sort();
+  println!(
+    "umd bundle of {} modules ({:.1} MB, {} exports): parse {}, analyze {} (min {}, max {})",
+    modules,
+    source.len() as f64 / 1024.0 / 1024.0,
+    exports,
+    format_ms(parse),
+    format_ms(times[times.len() / 2]),
+    format_ms(times[0]),
+    format_ms(times[times.len() - 1]),
+  );
+}
+
+fn format_ms(duration: Duration) -> String {
+  format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+// a UMD bundle with nested functions, blocks, branches and IIFE classes in every module
+fn generate_umd_bundle(modules: usize) -> String {
+  let mut source = String::from(
+    r#"(function (global, factory) {
+  typeof exports === 'object' && typeof module !== 'undefined' ? factory(exports) :
+  typeof define === 'function' && define.amd ? define(['exports'], factory) :
+  (global = global || self, factory(global.Lib = {}));
+}(this, function (exports) { 'use strict';"#,
+  );
+  for i in 0..modules {
+    source.push_str(&format!(
+      r#"
+  var config_{i} = {{ name: "m{i}", value: {i}, nested: {{ a: [1, 2, 3], b: "x" }} }};
+  function helper_{i}(a, b) {{
+    var local = a + b;
+    if (process.env.NODE_ENV !== "production") {{
+      console.warn("helper_{i}", local);
+      for (var k = 0; k < 10; k++) {{ local += k; }}
+    }} else {{
+      try {{ local = JSON.parse(JSON.stringify({{ local: local }})).local; }} catch (e) {{ local = 0; }}
+    }}
+    {{
+      var inner = function () {{ return [local, a, b].map(function (x) {{ return x * 2; }}); }};
+      local = inner().reduce(function (s, x) {{ return s + x; }}, 0);
+    }}
+    return local;
+  }}
+  var Klass_{i} = /** @class */ (function () {{
+    function Klass_{i}(x) {{ this.x = x; }}
+    Klass_{i}.prototype.get = function () {{ if (this.x) {{ return helper_{i}(this.x, 1); }} return 0; }};
+    return Klass_{i};
+  }}());
+  exports.helper_{i} = helper_{i};
+  exports.Klass_{i} = Klass_{i};"#
+    ));
+  }
+  source.push_str(
+    r#"
+  Object.defineProperty(exports, '__esModule', { value: true });
+}));"#,
+  );
+  source
+}
@@ -0,0 +1,172 @@
+use crate::format::{get_arrow_scope_names, get_fn_scope_names};
+use crate::lexer::{get_iife_body, get_iife_fn, with_expr_callee, with_require_call, Body};
+use swc_common::{Span, Spanned};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::visit::{Visit, VisitWith};
+
+/// A `require()` call that is found anywhere in the module.
+#[derive(Clone, Debug)]
+pub struct RequireCall {
+  /// the specifier, `None` if the argument is not a constant string.
+  pub specifier: Option<String>,
+  /// the span of the argument.
+  pub arg_span: Span,
+  /// the span of the call.
+  pub span: Span,
+  pub conditional: bool,
+  pub in_try: bool,
+}
+
+/// get every `require()` call of the module, including the calls in functions and conditional branches.
+pub fn get_require_calls(module: &Module) -> Vec<RequireCall> {
+  let mut visitor = RequireVisitor {
+    calls: vec![],
+    conditional: 0,
+    in_try: 0,
+    shadowed: 0,
+  };
+  module.visit_with(&mut visitor);
+  visitor.calls
+}
+
+struct RequireVisitor {
+  calls: Vec<RequireCall>,
+  /// the depth of the branches and functions that are not executed unconditionally.
+  conditional: usize,
+  /// the depth of the `try` blocks.
+  in_try: usize,
+  /// the depth of the functions that bind `require`, e.g. `function (require, module, exports) { ... }`.
+  shadowed: usize,
+}
+
+impl RequireVisitor {
+  fn visit_conditional<N: VisitWith<Self> + ?Sized>(&mut self, node: &N) {
+    self.conditional += 1;
+    node.visit_with(self);
+    self.conditional -= 1;
+  }
+
+  // visit the node in a function scope, `require` is shadowed in it if the function binds the name
+  fn visit_scope<N: VisitWith<Self> + ?Sized>(&mut self, names: Vec<String>, conditional: bool, node: &N) {
+    let shadows = names.iter().any(|name| name == "require");
+    self.shadowed += shadows as usize;
+    if conditional {
+      self.visit_conditional(node);
+    } else {
+      node.visit_with(self);
+    }
+    self.shadowed -= shadows as usize;
+  }
+}
+
+impl Visit for RequireVisitor {
+  fn visit_call_expr(&mut self, call: &CallExpr) {
+    if let Some(Expr::Ident(id)) = with_expr_callee(call) {
+      if id.sym.as_ref().eq("require") && self.shadowed == 0 && !call.args.is_empty() {
+        let arg = call.args[0].expr.as_ref();
+        let specifier = with_require_call(call).or_else(|| match arg {
+          // require(`./foo`)
+          Expr::Tpl(Tpl { exprs, quasis, .. }) if exprs.is_empty() => quasis
+            .first()
+            .and_then(|quasi| quasi.cooked.as_ref())
+            .map(|cooked| cooked.to_string_lossy().into_owned()),
+          _ => None,
+        });
+        self.calls.push(RequireCall {
+          specifier,
+          arg_span: arg.span(),
+          span: call.span,
+          conditional: self.conditional > 0,
+          in_try: self.in_try > 0,
+        });
+      }
+    }
+    // the body of an IIFE is executed unconditionally
+    if let (Some(body), Some(iife)) = (get_iife_body(call), get_iife_fn(call)) {
+      let names = match iife {
+        Expr::Fn(FnExpr { function, .. }) => get_fn_scope_names(function),
+        Expr::Arrow(arrow) => get_arrow_scope_names(arrow),
+        _ => vec![],
+      };
+      match body {
+        Body::Stmts(stmts) => self.visit_scope(names, false, stmts),
+        Body::Expr(expr) => self.visit_scope(names, false, expr),
+      }
+      call.args.visit_with(self);
+      return;
+    }
+    call.visit_children_with(self);
+  }
+
+  fn visit_function(&mut self, function: &Function) {
+    self.visit_scope(get_fn_scope_names(function), true, &function.body);
+  }
+
+  fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+    self.visit_scope(get_arrow_scope_names(arrow), true, &arrow.body);
+  }
+
+  fn visit_class(&mut self, class: &Class) {
+    class.super_class.visit_with(self);
+    self.visit_conditional(&class.body);
+  }
+
+  fn visit_if_stmt(&mut self, stmt: &IfStmt) {
+    stmt.test.visit_with(self);
+    self.visit_conditional(&stmt.cons);
+    self.visit_conditional(&stmt.alt);
+  }
+
+  fn visit_cond_expr(&mut self, expr: &CondExpr) {
+    expr.test.visit_with(self);
+    self.visit_conditional(&expr.cons);
+    self.visit_conditional(&expr.alt);
+  }
+
+  fn visit_bin_expr(&mut self, expr: &BinExpr) {
+    expr.left.visit_with(self);
+    if matches!(
+      expr.op,
+      BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing
+    ) {
+      self.visit_conditional(&expr.right);
+    } else {
+      expr.right.visit_with(self);
+    }
+  }
+
+  fn visit_switch_stmt(&mut self, stmt: &SwitchStmt) {
+    stmt.discriminant.visit_with(self);
+    self.visit_conditional(&stmt.cases);
+  }
+
+  fn visit_while_stmt(&mut self, stmt: &WhileStmt) {
+    stmt.test.visit_with(self);
+    self.visit_conditional(&stmt.body);
+  }
+
+  fn visit_for_stmt(&mut self, stmt: &ForStmt) {
+    stmt.init.visit_with(self);
+    stmt.test.visit_with(self);
+    self.visit_conditional(&stmt.update);
+    self.visit_conditional(&stmt.body);
+  }
+
+  fn visit_for_in_stmt(&mut self, stmt: &ForInStmt) {
+    stmt.right.visit_with(self);
+    self.visit_conditional(&stmt.body);
+  }
+
+  fn visit_for_of_stmt(&mut self, stmt: &ForOfStmt) {
+    stmt.right.visit_with(self);
+    self.visit_conditional(&stmt.body);
+  }
+
+  fn visit_try_stmt(&mut self, stmt: &TryStmt) {
+    self.in_try += 1;
+    stmt.block.visit_with(self);
+    self.in_try -= 1;
+    self.visit_conditional(&stmt.handler);
+    stmt.finalizer.visit_with(self);
+  }
+}
@@ -1,13 +1,15 @@
+use crate::result::SourceSpan;
 use std::fmt;
 use std::sync::{Arc, RwLock};
-use swc_common::errors::{Diagnostic, DiagnosticBuilder, Emitter};
+use swc_common::errors::{DiagnosticBuilder, DiagnosticId, Emitter, Level};
 use swc_common::{Loc, Span};
+use swc_ecmascript::parser::error::SyntaxError;
 
 /// A buffer for collecting errors from the AST parser.
 #[derive(Debug, Clone)]
 pub struct ErrorBuffer {
   specifier: String,
-  diagnostics: Arc<RwLock<Vec<Diagnostic>>>,
+  diagnostics: Arc<RwLock<Vec<swc_common::errors::Diagnostic>>>,
 }
 
 impl ErrorBuffer {
@@ -25,13 +27,266 @@ impl Emitter for ErrorBuffer {
   }
 }
 
-/// A buffer for collecting diagnostic messages from the AST parser.
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum Severity {
+  Error,
+  Warning,
+  Note,
+}
+
+impl From<Level> for Severity {
+  fn from(level: Level) -> Self {
+    match level {
+      Level::Warning => Severity::Warning,
+      Level::Note | Level::Help | Level::FailureNote => Severity::Note,
+      _ => Severity::Error,
+    }
+  }
+}
+
+/// A diagnostic of the AST parser, e.g. a syntax error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub message: String,
+  /// The kind of the error, e.g. `TS1109` or `Unexpected`.
+  pub code: Option<String>,
+  /// The specifier of the module.
+  pub file: String,
+  /// The (1-based) line number, `0` if the diagnostic has no location.
+  pub line: usize,
+  /// The (0-based) column, `0` if the diagnostic has no location.
+  pub column: usize,
+  /// The span of the source code that the diagnostic points to.
+  pub span: Option<SourceSpan>,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.span.is_some() {
+      write!(fmt, "{} at {}:{}:{}", self.message, self.file, self.line, self.column)
+    } else {
+      write!(fmt, "{}", self.message)
+    }
+  }
+}
+
+// match the syntax error kinds to their names, `{ .. }` matches unit, tuple and struct variants
+macro_rules! syntax_error_codes {
+  ($kind:expr, $($variant:ident),* $(,)?) => {
+    match $kind {
+      $(SyntaxError::$variant { .. } => stringify!($variant),)*
+      _ => "SyntaxError",
+    }
+  };
+}
+
+/// get the stable code of a syntax error, the name of its kind, e.g. `TS1109` or `Unexpected`.
+pub fn get_syntax_error_code(kind: &SyntaxError) -> &'static str {
+  syntax_error_codes!(
+    kind,
+    Eof,
+    DeclNotAllowed,
+    UsingDeclNotAllowed,
+    UsingDeclNotAllowedForForInLoop,
+    UsingDeclNotEnabled,
+    InvalidNameInUsingDecl,
+    InitRequiredForUsingDecl,
+    PrivateNameInInterface,
+    InvalidSuperCall,
+    InvalidSuper,
+    InvalidSuperPrivateName,
+    InvalidNewTarget,
+    InvalidImport,
+    ArrowNotAllowed,
+    ExportNotAllowed,
+    GetterSetterCannotBeReadonly,
+    GetterSetterCannotBeOptional,
+    GetterParam,
+    SetterParam,
+    TopLevelAwaitInScript,
+    LegacyDecimal,
+    LegacyOctal,
+    InvalidIdentChar,
+    ExpectedDigit,
+    SetterParamRequired,
+    RestPatInSetter,
+    UnterminatedBlockComment,
+    UnterminatedStrLit,
+    ExpectedUnicodeEscape,
+    EscapeInReservedWord,
+    UnterminatedRegExp,
+    UnterminatedTpl,
+    IdentAfterNum,
+    UnexpectedChar,
+    InvalidStrEscape,
+    InvalidUnicodeEscape,
+    BadCharacterEscapeSequence,
+    NumLitTerminatedWithExp,
+    LegacyCommentInModule,
+    InvalidIdentInStrict,
+    InvalidIdentInAsync,
+    EvalAndArgumentsInStrict,
+    ArgumentsInClassField,
+    IllegalLanguageModeDirective,
+    UnaryInExp,
+    Hash,
+    LineBreakInThrow,
+    LineBreakBeforeArrow,
+    Unexpected,
+    UnexpectedTokenWithSuggestions,
+    ReservedWordInImport,
+    AssignProperty,
+    Expected,
+    ExpectedSemiForExprStmt,
+    AwaitStar,
+    ReservedWordInObjShorthandOrPat,
+    NullishCoalescingWithLogicalOp,
+    MultipleDefault,
+    CommaAfterRestElement,
+    NonLastRestParam,
+    SpreadInParenExpr,
+    EmptyParenExpr,
+    InvalidPat,
+    InvalidExpr,
+    NotSimpleAssign,
+    InvalidAssignTarget,
+    ExpectedIdent,
+    ExpectedSemi,
+    DuplicateLabel,
+    AsyncGenerator,
+    NonTopLevelImportExport,
+    ImportExportInScript,
+    ImportMetaInScript,
+    PatVarWithoutInit,
+    WithInStrict,
+    ReturnNotAllowed,
+    TooManyVarInForInHead,
+    VarInitializerInForInHead,
+    LabelledGeneratorOrAsync,
+    LabelledFunctionInStrict,
+    YieldParamInGen,
+    AwaitParamInAsync,
+    AwaitForStmt,
+    AwaitInFunction,
+    UnterminatedJSXContents,
+    EmptyJSXAttr,
+    InvalidJSXValue,
+    JSXExpectedClosingTagForLtGt,
+    JSXExpectedClosingTag,
+    InvalidLeadingDecorator,
+    DecoratorOnExport,
+    TsRequiredAfterOptional,
+    TsInvalidParamPropPat,
+    SpaceBetweenHashAndIdent,
+    AsyncConstructor,
+    PropertyNamedConstructor,
+    PrivateConstructor,
+    PrivateNameModifier,
+    ConstructorAccessor,
+    ReadOnlyMethod,
+    GeneratorConstructor,
+    DuplicateConstructor,
+    TsBindingPatCannotBeOptional,
+    SuperCallOptional,
+    OptChainCannotFollowConstructorCall,
+    TaggedTplInOptChain,
+    TrailingCommaInsideImport,
+    ExportDefaultWithOutFrom,
+    ExportExpectFrom,
+    DotsWithoutIdentifier,
+    NumericSeparatorIsAllowedOnlyBetweenTwoDigits,
+    ImportBindingIsString,
+    ExportBindingIsString,
+    ConstDeclarationsRequireInitialization,
+    DuplicatedRegExpFlags,
+    UnknownRegExpFlags,
+    TS1003,
+    TS1005,
+    TS1009,
+    TS1014,
+    TS1015,
+    TS1029,
+    TS1030,
+    TS1031,
+    TS1038,
+    TS1042,
+    TS1047,
+    TS1048,
+    TS1056,
+    TS1085,
+    TS1089,
+    TS1092,
+    TS1096,
+    TS1098,
+    TS1100,
+    TS1102,
+    TS1105,
+    TS1106,
+    TS1107,
+    TS1109,
+    TS1110,
+    TS1114,
+    TS1115,
+    TS1116,
+    TS1123,
+    TS1141,
+    TS1162,
+    TS1164,
+    TS1171,
+    TS1172,
+    TS1173,
+    TS1174,
+    TS1175,
+    TS1183,
+    TS1184,
+    TS1185,
+    TS1093,
+    TS1196,
+    TS1242,
+    TS1243,
+    TS1244,
+    TS1245,
+    TS1267,
+    TS1273,
+    TS1274,
+    TS1277,
+    TS2206,
+    TS2207,
+    TS2369,
+    TS2371,
+    TS2406,
+    TS2410,
+    TS2414,
+    TS2427,
+    TS2452,
+    TS2483,
+    TS2491,
+    TS2499,
+    TS2703,
+    TS4112,
+    TS8038,
+    TS18010,
+    TSTypeAnnotationAfterAssign,
+    TsNonNullAssertionNotAllowed,
+    WithLabel,
+    ReservedTypeAssertion,
+    ReservedArrowTypeParam,
+    EmptyTypeArgumentList
+  )
+}
+
+/// A buffer for collecting diagnostics from the AST parser.
 #[derive(Debug)]
-pub struct DiagnosticBuffer(Vec<String>);
+pub struct DiagnosticBuffer(Vec<Diagnostic>);
 
 impl fmt::Display for DiagnosticBuffer {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-    fmt.pad(&self.0.join(","))
+    let messages = self.0.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+    fmt.pad(&messages.join(","))
   }
 }
 
@@ -44,18 +299,48 @@ impl DiagnosticBuffer {
     let diagnostics = diagnostics
       .iter()
       .map(|d| {
-        let mut message = d.message();
+        let code = d.code.as_ref().map(|code| match code {
+          DiagnosticId::Error(code) | DiagnosticId::Lint(code) => code.clone(),
+        });
+        let mut diagnostic = Diagnostic {
+          severity: d.level.into(),
+          message: d.message(),
+          code,
+          file: error_buffer.specifier.clone(),
+          line: 0,
+          column: 0,
+          span: None,
+        };
         if let Some(span) = d.span.primary_span() {
           let loc = get_loc(span);
-          message = format!(
-            "{} at {}:{}:{}",
-            message, error_buffer.specifier, loc.line, loc.col_display
-          );
+          diagnostic.line = loc.line;
+          diagnostic.column = loc.col_display;
+          diagnostic.span = Some(SourceSpan {
+            start: span.lo.0 - loc.file.start_pos.0,
+            end: span.hi.0 - loc.file.start_pos.0,
+            line: loc.line,
+            column: loc.col_display,
+          });
         }
-        message
+        diagnostic
       })
       .collect();
 
     Self(diagnostics)
   }
+
+  /// check if there is no diagnostic.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// get the diagnostics.
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self.0
+  }
+
+  /// get the diagnostics, consuming the buffer.
+  pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+    self.0
+  }
 }
@@ -0,0 +1,250 @@
+use crate::result::ModuleFormat;
+use std::collections::HashSet;
+use swc_ecmascript::ast::*;
+use swc_ecmascript::visit::{Visit, VisitWith};
+
+/// The format and the ESM exports of a module, they don't depend on the analyze options.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleSyntax {
+  pub format: ModuleFormat,
+  /// the names that are exported by the ESM `export` statements.
+  pub esm_exports: Vec<String>,
+  /// the sources of `export * from "..."`.
+  pub esm_reexports: Vec<String>,
+}
+
+impl ModuleSyntax {
+  pub fn from_module(module: &Module) -> Self {
+    let mut esm_exports: Vec<String> = vec![];
+    let mut esm_reexports: Vec<String> = vec![];
+    let mut declared: HashSet<String> = HashSet::new();
+    let mut has_module_decl = false;
+    let mut loader: Option<ModuleFormat> = None;
+    for item in &module.body {
+      match item {
+        ModuleItem::ModuleDecl(decl) => {
+          has_module_decl = true;
+          match decl {
+            // import foo from "foo"
+            ModuleDecl::Import(ImportDecl { specifiers, .. }) => {
+              for specifier in specifiers {
+                declared.insert(specifier.local().sym.as_ref().to_owned());
+              }
+            }
+            // export const foo = 1
+            // export function foo() {}
+            ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => {
+              let mut names = vec![];
+              get_decl_names(decl, &mut names);
+              for name in names {
+                declared.insert(name.clone());
+                push_unique(&mut esm_exports, name);
+              }
+            }
+            // export { foo, bar as baz }
+            // export * as ns from "foo"
+            ModuleDecl::ExportNamed(NamedExport { specifiers, .. }) => {
+              for specifier in specifiers {
+                let name = match specifier {
+                  ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => name.atom().to_string(),
+                  ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => exported.sym.as_ref().to_owned(),
+                  ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => {
+                    exported.as_ref().unwrap_or(orig).atom().to_string()
+                  }
+                };
+                push_unique(&mut esm_exports, name);
+              }
+            }
+            // export default foo
+            ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+              push_unique(&mut esm_exports, "default".to_owned());
+            }
+            // export * from "foo"
+            ModuleDecl::ExportAll(ExportAll { src, .. }) => {
+              push_unique(&mut esm_reexports, src.value.to_string_lossy().into_owned());
+            }
+            _ => {}
+          }
+        }
+        ModuleItem::Stmt(Stmt::Decl(decl)) => {
+          let mut names = vec![];
+          get_decl_names(decl, &mut names);
+          declared.extend(names);
+        }
+        // define([...], function (...) { ... })
+        // System.register([...], function (...) { ... })
+        ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) if loader.is_none() => {
+          loader = get_loader_format(expr);
+        }
+        _ => {}
+      }
+    }
+    if loader == Some(ModuleFormat::Amd) && declared.contains("define") {
+      loader = None;
+    }
+
+    let mut visitor = FormatVisitor {
+      declared: &declared,
+      scopes: vec![],
+      commonjs: false,
+      import_meta: false,
+    };
+    module.visit_with(&mut visitor);
+    let esm = has_module_decl || visitor.import_meta;
+    let format = match (esm, visitor.commonjs, loader) {
+      (true, true, _) => ModuleFormat::Mixed,
+      (true, false, _) => ModuleFormat::Esm,
+      (false, _, Some(loader)) => loader,
+      (false, true, None) => ModuleFormat::Cjs,
+      (false, false, None) => ModuleFormat::Unknown,
+    };
+    ModuleSyntax {
+      format,
+      esm_exports,
+      esm_reexports,
+    }
+  }
+}
+
+// find the references of the commonjs globals and `import.meta`
+struct FormatVisitor<'a> {
+  /// the top-level names that shadow the commonjs globals, e.g. `const require = createRequire(import.meta.url)`.
+  declared: &'a HashSet<String>,
+  /// the names of the enclosing function scopes, e.g. `function (require, module, exports) { ... }`.
+  scopes: Vec<Vec<String>>,
+  commonjs: bool,
+  import_meta: bool,
+}
+
+impl FormatVisitor<'_> {
+  fn is_shadowed(&self, name: &str) -> bool {
+    self.declared.contains(name) || self.scopes.iter().any(|names| names.iter().any(|n| n == name))
+  }
+}
+
+impl Visit for FormatVisitor<'_> {
+  fn visit_expr(&mut self, expr: &Expr) {
+    if let Expr::Ident(id) = expr {
+      let name = id.sym.as_ref();
+      if matches!(name, "require" | "module" | "exports") && !self.is_shadowed(name) {
+        self.commonjs = true;
+      }
+    }
+    expr.visit_children_with(self);
+  }
+
+  fn visit_function(&mut self, function: &Function) {
+    self.scopes.push(get_fn_scope_names(function));
+    function.visit_children_with(self);
+    self.scopes.pop();
+  }
+
+  fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+    self.scopes.push(get_arrow_scope_names(arrow));
+    arrow.visit_children_with(self);
+    self.scopes.pop();
+  }
+
+  fn visit_meta_prop_expr(&mut self, expr: &MetaPropExpr) {
+    if expr.kind == MetaPropKind::ImportMeta {
+      self.import_meta = true;
+    }
+  }
+}
+
+// get the format of a top-level `define(...)` or `System.register(...)` call
+fn get_loader_format(expr: &Expr) -> Option<ModuleFormat> {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => get_loader_format(expr),
+    Expr::Call(CallExpr {
+      callee: Callee::Expr(callee),
+      args,
+      ..
+    }) if !args.is_empty() => match callee.as_ref() {
+      Expr::Ident(id) if id.sym.as_ref().eq("define") => Some(ModuleFormat::Amd),
+      Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(prop),
+        ..
+      }) if prop.sym.as_ref().eq("register")
+        && matches!(obj.as_ref(), Expr::Ident(id) if id.sym.as_ref().eq("System")) =>
+      {
+        Some(ModuleFormat::System)
+      }
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+fn get_decl_names(decl: &Decl, names: &mut Vec<String>) {
+  match decl {
+    Decl::Var(var) => {
+      for decl in &var.decls {
+        get_pat_names(&decl.name, names);
+      }
+    }
+    Decl::Fn(FnDecl { ident, .. }) | Decl::Class(ClassDecl { ident, .. }) => {
+      names.push(ident.sym.as_ref().to_owned());
+    }
+    _ => {}
+  }
+}
+
+/// get the names that are bound in the scope of a function, the params and the top-level declarations of its body.
+pub(crate) fn get_fn_scope_names(function: &Function) -> Vec<String> {
+  let stmts = function.body.as_ref().map_or(&[][..], |body| &body.stmts);
+  get_scope_names(function.params.iter().map(|param| &param.pat), stmts)
+}
+
+/// get the names that are bound in the scope of an arrow function.
+pub(crate) fn get_arrow_scope_names(arrow: &ArrowExpr) -> Vec<String> {
+  let stmts = match arrow.body.as_ref() {
+    BlockStmtOrExpr::BlockStmt(BlockStmt { stmts, .. }) => &stmts[..],
+    _ => &[],
+  };
+  get_scope_names(arrow.params.iter(), stmts)
+}
+
+fn get_scope_names<'a>(params: impl Iterator<Item = &'a Pat>, stmts: &[Stmt]) -> Vec<String> {
+  let mut names = vec![];
+  for param in params {
+    get_pat_names(param, &mut names);
+  }
+  for stmt in stmts {
+    if let Stmt::Decl(decl) = stmt {
+      get_decl_names(decl, &mut names);
+    }
+  }
+  names
+}
+
+// const { a, b: [c] } = ...
+fn get_pat_names(pat: &Pat, names: &mut Vec<String>) {
+  match pat {
+    Pat::Ident(id) => names.push(id.sym.as_ref().to_owned()),
+    Pat::Array(ArrayPat { elems, .. }) => {
+      for elem in elems.iter().flatten() {
+        get_pat_names(elem, names);
+      }
+    }
+    Pat::Object(ObjectPat { props, .. }) => {
+      for prop in props {
+        match prop {
+          ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => get_pat_names(value, names),
+          ObjectPatProp::Assign(AssignPatProp { key, .. }) => names.push(key.sym.as_ref().to_owned()),
+          ObjectPatProp::Rest(RestPat { arg, .. }) => get_pat_names(arg, names),
+        }
+      }
+    }
+    Pat::Rest(RestPat { arg, .. }) => get_pat_names(arg, names),
+    Pat::Assign(AssignPat { left, .. }) => get_pat_names(left, names),
+    _ => {}
+  }
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+  if !list.contains(&value) {
+    list.push(value);
+  }
+}
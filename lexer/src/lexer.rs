@@ -1,48 +1,173 @@
-use indexmap::{IndexMap, IndexSet};
-use swc_common::DUMMY_SP;
+use crate::result::{DefaultKind, Provenance, WarningKind};
+use crate::scope::ScopedMap;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use swc_common::{Span, Spanned, DUMMY_SP};
 use swc_ecmascript::ast::*;
-use swc_ecmascript::visit::{noop_fold_type, Fold};
+use swc_ecmascript::visit::{Visit, VisitWith};
+
+/// the value of the properties that are assigned to an object later, e.g. `foo.bar = ...`.
+static ASSIGNED_PROP_VALUE: Expr = Expr::Lit(Lit::Bool(Bool {
+  span: DUMMY_SP,
+  value: true,
+}));
 
 #[derive(Clone, Debug)]
-pub enum IdentKind {
+pub enum IdentKind<'a> {
   Lit(Lit),
   Alias(String),
-  Object(Vec<PropOrSpread>),
-  Class(Class),
-  Fn(FnDesc),
+  Array(&'a [Option<ExprOrSpread>]),
+  Object(ObjDesc<'a>),
+  Class(&'a Class),
+  Fn(FnDesc<'a>),
   Reexport(Reexport),
   Unkonwn,
 }
 
+/// The statements of a function or a block, borrowed from the module.
+#[derive(Clone, Copy, Debug)]
+pub enum Body<'a> {
+  Stmts(&'a [Stmt]),
+  /// the expression body of an arrow function, e.g. `() => ({ foo })`.
+  Expr(&'a Expr),
+}
+
 #[derive(Clone, Debug)]
-pub struct FnDesc {
-  stmts: Vec<Stmt>,
+pub struct ObjDesc<'a> {
+  props: &'a [PropOrSpread],
+  /// the names that are assigned to the object later, e.g. `foo.bar = ...`.
   extends: Vec<String>,
+  /// the names that are defined on the object by a bundler helper, e.g. `__webpack_require__.d(foo, { bar: ... })`.
+  getters: Vec<(String, Provenance)>,
+  /// the modules that are copied to the object later, e.g. `__reExport(foo, require("bar"))`.
+  reexports: Vec<Reexport>,
 }
 
 #[derive(Clone, Debug)]
-pub struct Reexport {
-  path: String,
+pub struct FnDesc<'a> {
+  body: Body<'a>,
   extends: Vec<String>,
+  constructible: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Reexport {
+  pub specifier: String,
+  pub called: bool,
+  pub member: Option<String>,
+  pub extends: Vec<String>,
 }
 
-pub struct ModuleLexer {
-  pub node_env: String,
+impl Reexport {
+  fn new(specifier: String) -> Self {
+    Reexport {
+      specifier,
+      called: false,
+      member: None,
+      extends: vec![],
+    }
+  }
+
+  fn is_same(&self, other: &Reexport) -> bool {
+    self.specifier == other.specifier && self.called == other.called && self.member == other.member
+  }
+}
+
+pub struct ModuleLexer<'a> {
+  /// global expressions that are replaced with constant values, e.g. `process.env.NODE_ENV`.
+  pub defines: HashMap<String, Lit>,
   pub call_mode: bool,
   pub fn_returned: bool,
-  pub exports_alias: IndexSet<String>,
-  pub idents: IndexMap<String, IdentKind>,
-  pub named_exports: IndexSet<String>,
-  pub reexports: IndexSet<String>,
+  /// the names that are bound to `exports`, scoped to the body that is being walked.
+  pub exports_alias: ScopedMap<()>,
+  /// the known values of the local variables, scoped to the body that is being walked.
+  pub idents: ScopedMap<IdentKind<'a>>,
+  pub named_exports: IndexMap<String, (Span, Provenance)>,
+  pub reexports: Vec<(Reexport, Span, Provenance)>,
+  /// the named exports that are bound to a member of a reexported module, e.g. `foo` of `require("./foo").foo`.
+  pub named_reexports: IndexMap<String, (Reexport, Span, Provenance)>,
+  /// the span of the statement that is being walked.
+  pub span: Span,
+  /// whether the walker is in an annotation that is never executed, e.g. `0 && (module.exports = { foo })`.
+  pub annotation: bool,
+  /// what `module.exports` is bound to.
+  pub default_kind: DefaultKind,
+  /// whether `module.exports` can be called with `new`.
+  pub constructible: bool,
+  /// the export patterns that could not be resolved.
+  pub warnings: Vec<(WarningKind, Span)>,
+  /// the names that are bound to the webpack runtime, e.g. `var e = { d: (t, o) => { ... } }`.
+  pub webpack_runtimes: &'a HashSet<String>,
+  /// the names that are mutated as arrays anywhere in the module, e.g. `names.push(name)`.
+  pub mutated_arrays: &'a HashSet<String>,
 }
 
-impl ModuleLexer {
+impl<'a> ModuleLexer<'a> {
   fn clear(&mut self) {
     self.named_exports.clear();
     self.reexports.clear();
+    self.named_reexports.clear();
+  }
+
+  fn add_export(&mut self, name: String, provenance: Provenance) {
+    // the last assignment wins, e.g. `exports.foo = require("./foo").foo; exports.foo = 1;`
+    if !self.annotation {
+      self.named_reexports.shift_remove(&name);
+    }
+    let provenance = self.provenance(provenance);
+    self.named_exports.entry(name).or_insert((self.span, provenance));
+  }
+
+  fn add_named_reexport(&mut self, name: String, reexport: Reexport, provenance: Provenance) {
+    self.add_export(name.clone(), provenance);
+    let provenance = self.provenance(provenance);
+    self.named_reexports.insert(name, (reexport, self.span, provenance));
+  }
+
+  // add the export, or the named reexport if the value is a reexported module or its member
+  fn add_export_with_value(&mut self, name: String, value: &Expr, provenance: Provenance) {
+    match self.as_named_reexport(value) {
+      Some(reexport) => self.add_named_reexport(name, reexport, provenance),
+      None => self.add_export(name, provenance),
+    }
+  }
+
+  fn add_reexport(&mut self, reexport: Reexport, provenance: Provenance) {
+    let provenance = self.provenance(provenance);
+    if let Some((existing, _, _)) = self.reexports.iter_mut().find(|(r, _, _)| r.is_same(&reexport)) {
+      for name in reexport.extends {
+        if !existing.extends.contains(&name) {
+          existing.extends.push(name);
+        }
+      }
+    } else {
+      self.reexports.push((reexport, self.span, provenance));
+    }
+  }
+
+  fn add_warning(&mut self, kind: WarningKind, span: Span) {
+    // the annotation is never executed
+    if !self.annotation {
+      self.warnings.push((kind, span));
+    }
+  }
+
+  fn provenance(&self, provenance: Provenance) -> Provenance {
+    if self.annotation {
+      Provenance::EsbuildAnnotation
+    } else {
+      provenance
+    }
+  }
+
+  fn enter_stmt(&mut self, stmt: &Stmt) {
+    let span = stmt.span();
+    if !span.is_dummy() {
+      self.span = span;
+    }
   }
 
-  fn replace_exports_from_expr(&mut self, expr: &Expr) {
+  fn replace_exports_from_expr(&mut self, expr: &'a Expr) {
     if let Expr::Paren(ParenExpr { expr, .. }) = expr {
       self.replace_exports_from_expr(expr);
       return;
@@ -50,111 +175,154 @@ impl ModuleLexer {
 
     if let Some(reexport) = self.as_reexport(expr) {
       self.clear();
-      for name in reexport.extends {
-        self.named_exports.insert(name);
+      self.set_default_kind(DefaultKind::Reexport, false);
+      for name in &reexport.extends {
+        self.add_export(name.clone(), Provenance::ReexportProperty);
       }
-      self.reexports.insert(reexport.path);
+      self.add_reexport(reexport, Provenance::ModuleExports);
     } else if let Some(props) = self.as_obj(expr) {
       self.clear();
-      self.update_exports_from_object(props);
+      self.set_default_kind(DefaultKind::Object, false);
+      self.update_exports_from_object(props, Provenance::ObjectLiteral);
+    } else if let Some(props) = self.as_to_commonjs(expr) {
+      // module.exports = __toCommonJS(src_exports)
+      self.clear();
+      self.set_default_kind(DefaultKind::Object, false);
+      self.add_export("__esModule".to_owned(), Provenance::EsbuildExport);
+      self.update_exports_from_object(props, Provenance::EsbuildExport);
     } else if let Some(class) = self.as_class(expr) {
       self.clear();
-      for name in get_class_static_names(&class) {
-        self.named_exports.insert(name);
-      }
-    } else if let Some(FnDesc { stmts, extends }) = self.as_function(expr) {
+      self.set_default_kind(DefaultKind::Class, true);
+      for name in get_class_static_names(class) {
+        self.add_export(name, Provenance::ClassStatic);
+      }
+    } else if let Some(FnDesc {
+      body,
+      extends,
+      constructible,
+    }) = self.as_function(expr)
+    {
       self.clear();
+      self.set_default_kind(DefaultKind::Function, constructible);
       if self.call_mode {
-        self.walk_body(stmts, true);
+        self.walk_body(body, true);
       } else {
         for name in extends {
-          self.named_exports.insert(name);
+          self.add_export(name, Provenance::FunctionProperty);
         }
       }
-    } else if let Expr::Call(call) = expr {
-      if call.args.len() == 0 {
-        if let Some(callee) = with_expr_callee(call) {
-          if let Some(reexport) = self.as_reexport(callee) {
-            self.clear();
-            for name in reexport.extends {
-              self.named_exports.insert(name);
+    } else if self.is_primitive(expr) {
+      self.clear();
+      self.set_default_kind(DefaultKind::Primitive, false);
+    } else if let Expr::Assign(assign) = expr {
+      self.replace_exports_from_expr(&assign.right);
+    } else {
+      self.set_default_kind(DefaultKind::Unknown, false);
+      if let Expr::Call(call) = expr {
+        if call.args.is_empty() {
+          if let Some(callee) = with_expr_callee(call) {
+            if let Some(FnDesc { body, .. }) = self.as_function(callee) {
+              self.walk_body(body, true);
+              return;
             }
-            self.reexports.insert(format!("{}()", reexport.path));
-          } else if let Some(FnDesc { stmts, .. }) = self.as_function(callee) {
-            self.walk_body(stmts, true);
           }
         }
       }
-    } else if let Expr::Assign(assign) = expr {
-      self.replace_exports_from_expr(&assign.right);
+      self.add_warning(WarningKind::UnknownModuleExports, expr.span());
     }
   }
 
-  fn mark_ident(&mut self, name: &str, expr: &Expr) {
+  fn set_default_kind(&mut self, kind: DefaultKind, constructible: bool) {
+    self.default_kind = kind;
+    self.constructible = constructible;
+  }
+
+  fn mark_ident(&mut self, name: &str, expr: &'a Expr) {
     if let Expr::Paren(ParenExpr { expr, .. }) = expr {
       self.mark_ident(name, expr);
       return;
     }
+    // var exportStar = (this && this.__exportStar) || function (m, exports) { ... }
+    if let Some(helper) = get_this_helper_name(expr) {
+      if helper.ne(name) {
+        self.idents.insert(name.into(), IdentKind::Alias(helper.into()));
+        return;
+      }
+    }
     match expr {
+      // var isBrowser = typeof window !== "undefined" && typeof document !== "undefined"
+      Expr::Bin(_) | Expr::Unary(UnaryExpr { op: UnaryOp::Bang, .. }) => {
+        if let Some(value) = self.as_known_bool(expr) {
+          self
+            .idents
+            .insert(name.into(), IdentKind::Lit(Lit::Bool(Bool { span: DUMMY_SP, value })));
+        } else {
+          self.idents.insert(name.into(), IdentKind::Unkonwn);
+        }
+      }
+      // var isBrowser = typeof window
+      Expr::Unary(UnaryExpr {
+        op: UnaryOp::TypeOf, ..
+      }) => {
+        if let Some(lit) = self.as_define(expr) {
+          self.idents.insert(name.into(), IdentKind::Lit(lit.clone()));
+        } else {
+          self.idents.insert(name.into(), IdentKind::Unkonwn);
+        }
+      }
       Expr::Lit(lit) => {
         self.idents.insert(name.into(), IdentKind::Lit(lit.clone()));
       }
       Expr::Ident(id) => {
-        let conflict = if let Some(val) = self.idents.get(id.sym.as_ref()) {
-          if let IdentKind::Alias(rename) = val {
-            rename.eq(name)
-          } else {
-            false
-          }
-        } else {
-          false
-        };
+        let conflict = matches!(self.idents.get(id.sym.as_ref()), Some(IdentKind::Alias(rename)) if rename.eq(name));
         if !conflict {
           self
             .idents
             .insert(name.into(), IdentKind::Alias(id.sym.as_ref().into()));
         }
       }
-      Expr::Call(call) => {
-        if let Some(path) = with_require_call(&call) {
-          self
-            .idents
-            .insert(name.into(), IdentKind::Reexport(Reexport { path, extends: vec![] }));
+      Expr::Call(_) => {
+        if let Some(reexport) = self.as_reexport(expr) {
+          self.idents.insert(name.into(), IdentKind::Reexport(reexport));
         }
       }
       Expr::Object(obj) => {
-        self.idents.insert(name.into(), IdentKind::Object(obj.props.clone()));
+        self.idents.insert(
+          name.into(),
+          IdentKind::Object(ObjDesc {
+            props: &obj.props,
+            extends: vec![],
+            getters: vec![],
+            reexports: vec![],
+          }),
+        );
+      }
+      // the elements of a mutated array are unknown, e.g. `names.push(name)`
+      Expr::Array(_) if self.mutated_arrays.contains(name) => {
+        self.idents.insert(name.into(), IdentKind::Unkonwn);
+      }
+      Expr::Array(ArrayLit { elems, .. }) => {
+        self.idents.insert(name.into(), IdentKind::Array(elems));
       }
       Expr::Class(ClassExpr { class, .. }) => {
-        self
-          .idents
-          .insert(name.into(), IdentKind::Class(class.as_ref().clone()));
+        self.idents.insert(name.into(), IdentKind::Class(class));
       }
       Expr::Arrow(arrow) => {
         self.idents.insert(
           name.into(),
           IdentKind::Fn(FnDesc {
-            stmts: get_arrow_body_as_stmts(&arrow),
+            body: get_arrow_body(arrow),
             extends: vec![],
+            constructible: false,
           }),
         );
       }
-      Expr::Fn(FnExpr { function, .. }) => {
-        if let Function { body: Some(body), .. } = function.as_ref() {
-          self.idents.insert(
-            name.into(),
-            IdentKind::Fn(FnDesc {
-              stmts: body.stmts.clone(),
-              extends: vec![],
-            }),
-          );
-        };
-      }
+      Expr::Fn(FnExpr { function, .. }) => self.mark_function(name, function),
       Expr::Member(_) => {
-        if is_member_member(expr, "process", "env", "NODE_ENV") {
-          self
-            .idents
-            .insert(name.into(), IdentKind::Lit(Lit::Str(quote_str(self.node_env.as_str()))));
+        if let Some(lit) = self.as_define(expr) {
+          self.idents.insert(name.into(), IdentKind::Lit(lit.clone()));
+        } else if let Some(reexport) = self.as_reexport(expr) {
+          self.idents.insert(name.into(), IdentKind::Reexport(reexport));
         }
       }
       _ => {
@@ -163,7 +331,26 @@ impl ModuleLexer {
     };
   }
 
+  fn mark_function(&mut self, name: &str, function: &'a Function) {
+    if let Some(body) = &function.body {
+      self.idents.insert(
+        name.into(),
+        IdentKind::Fn(FnDesc {
+          body: Body::Stmts(&body.stmts),
+          extends: vec![],
+          constructible: is_constructible_function(function),
+        }),
+      );
+    }
+  }
+
   fn as_str(&self, expr: &Expr) -> Option<String> {
+    if let Some(lit) = self.as_define(expr) {
+      return match lit {
+        Lit::Str(Str { value, .. }) => Some(value.to_string_lossy().into_owned()),
+        _ => None,
+      };
+    }
     match expr {
       Expr::Paren(ParenExpr { expr, .. }) => return self.as_str(expr),
       Expr::Lit(Lit::Str(Str { value, .. })) => return Some(value.to_string_lossy().into_owned()),
@@ -176,17 +363,18 @@ impl ModuleLexer {
           }
         }
       }
-      Expr::Member(_) => {
-        if is_member_member(expr, "process", "env", "NODE_ENV") {
-          return Some(self.node_env.to_owned());
-        }
-      }
       _ => {}
     };
     None
   }
 
   fn as_num(&self, expr: &Expr) -> Option<f64> {
+    if let Some(lit) = self.as_define(expr) {
+      return match lit {
+        Lit::Num(Number { value, .. }) => Some(*value),
+        _ => None,
+      };
+    }
     match expr {
       Expr::Paren(ParenExpr { expr, .. }) => return self.as_num(expr),
       Expr::Lit(Lit::Num(Number { value, .. })) => return Some(*value),
@@ -205,6 +393,12 @@ impl ModuleLexer {
   }
 
   fn as_bool(&self, expr: &Expr) -> Option<bool> {
+    if let Some(lit) = self.as_define(expr) {
+      return match lit {
+        Lit::Bool(Bool { value, .. }) => Some(*value),
+        _ => None,
+      };
+    }
     match expr {
       Expr::Paren(ParenExpr { expr, .. }) => return self.as_bool(expr),
       Expr::Lit(Lit::Bool(Bool { value, .. })) => return Some(*value),
@@ -223,6 +417,12 @@ impl ModuleLexer {
   }
 
   fn as_null(&self, expr: &Expr) -> Option<bool> {
+    if let Some(lit) = self.as_define(expr) {
+      return match lit {
+        Lit::Null(_) => Some(true),
+        _ => None,
+      };
+    }
     match expr {
       Expr::Paren(ParenExpr { expr, .. }) => return self.as_null(expr),
       Expr::Lit(Lit::Null(_)) => return Some(true),
@@ -240,33 +440,186 @@ impl ModuleLexer {
     None
   }
 
-  fn as_obj(&self, expr: &Expr) -> Option<Vec<PropOrSpread>> {
+  // "foo", 1, true, null, undefined, void 0, `foo`
+  fn is_primitive(&self, expr: &Expr) -> bool {
     match expr {
-      Expr::Paren(ParenExpr { expr, .. }) => return self.as_obj(expr),
-      Expr::Object(ObjectLit { props, .. }) => Some(props.to_vec()),
-      Expr::Ident(id) => {
-        if let Some(value) = self.idents.get(id.sym.as_ref()) {
-          match value {
-            IdentKind::Object(props) => return Some(props.to_vec()),
-            IdentKind::Alias(id) => return self.as_obj(&Expr::Ident(quote_ident(id))),
-            _ => {}
-          }
-        }
-        None
+      Expr::Paren(ParenExpr { expr, .. }) => self.is_primitive(expr),
+      Expr::Lit(lit) => !matches!(lit, Lit::Regex(_) | Lit::JSXText(_)),
+      Expr::Tpl(_) => true,
+      Expr::Unary(UnaryExpr { op, .. }) => !matches!(op, UnaryOp::Delete),
+      Expr::Ident(id) => match self.idents.get(id.sym.as_ref()) {
+        Some(IdentKind::Lit(_)) => true,
+        Some(IdentKind::Alias(id)) => self.is_primitive(&Expr::Ident(quote_ident(id))),
+        Some(_) => false,
+        None => id.sym.as_ref().eq("undefined"),
+      },
+      _ => false,
+    }
+  }
+
+  fn as_lit(&self, expr: &Expr) -> Option<Lit> {
+    if let Some(lit) = self.as_define(expr) {
+      return Some(lit.clone());
+    }
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_lit(expr),
+      Expr::Lit(lit) => Some(lit.clone()),
+      Expr::Ident(id) => match self.idents.get(id.sym.as_ref()) {
+        Some(IdentKind::Lit(lit)) => Some(lit.clone()),
+        Some(IdentKind::Alias(id)) => self.as_lit(&Expr::Ident(quote_ident(id))),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  // process.env.NODE_ENV
+  // typeof window
+  // __DEV__
+  fn as_define(&self, expr: &Expr) -> Option<&Lit> {
+    if let Expr::Paren(ParenExpr { expr, .. }) = expr {
+      return self.as_define(expr);
+    }
+    if !matches!(expr, Expr::Ident(_) | Expr::Member(_) | Expr::Unary(_)) {
+      return None;
+    }
+    self.lookup_define(&get_define_key(expr)?)
+  }
+
+  fn lookup_define(&self, key: &str) -> Option<&Lit> {
+    let root = key.trim_start_matches("typeof ").split('.').next().unwrap_or_default();
+    // the global is shadowed by a local variable, except `var process = require("process")`
+    match self.idents.get(root) {
+      Some(IdentKind::Reexport(reexport)) if is_builtin_reexport(reexport, root) => {}
+      Some(_) => return None,
+      None => {}
+    }
+    self.defines.get(key)
+  }
+
+  fn as_obj(&self, expr: &'a Expr) -> Option<ObjDesc<'a>> {
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_obj(expr),
+      Expr::Object(ObjectLit { props, .. }) => Some(ObjDesc {
+        props,
+        extends: vec![],
+        getters: vec![],
+        reexports: vec![],
+      }),
+      Expr::Ident(id) => self.as_obj_ident(id.sym.as_ref()),
+      _ => None,
+    }
+  }
+
+  // __toCommonJS(src_exports)
+  fn as_to_commonjs(&self, expr: &'a Expr) -> Option<ObjDesc<'a>> {
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_to_commonjs(expr),
+      Expr::Call(call) if is_helper_call(call, "__toCommonJS") && call.args.len() == 1 => {
+        self.as_obj(call.args[0].expr.as_ref())
+      }
+      _ => None,
+    }
+  }
+
+  fn as_obj_ident(&self, name: &str) -> Option<ObjDesc<'a>> {
+    match self.idents.get(name)? {
+      IdentKind::Object(desc) => Some(desc.clone()),
+      IdentKind::Alias(id) => self.as_obj_ident(id),
+      _ => None,
+    }
+  }
+
+  // ["foo", "bar"]
+  // Object.keys({ foo, bar })
+  fn as_str_array(&self, expr: &'a Expr) -> Option<Vec<String>> {
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_str_array(expr),
+      Expr::Array(ArrayLit { elems, .. }) => self.as_str_elems(elems),
+      Expr::Ident(id) => self.as_str_array_ident(id.sym.as_ref()),
+      Expr::Call(call) if is_object_static_mothod_call(call, "keys") && call.args.len() == 1 => {
+        self.as_obj(&call.args[0].expr).map(|desc| get_obj_keys(&desc))
       }
       _ => None,
     }
   }
 
+  fn as_str_array_ident(&self, name: &str) -> Option<Vec<String>> {
+    match self.idents.get(name)? {
+      IdentKind::Array(elems) => self.as_str_elems(elems),
+      IdentKind::Alias(id) => self.as_str_array_ident(id),
+      _ => None,
+    }
+  }
+
+  // returns `None` if any element is not a constant string, the list would be incomplete
+  fn as_str_elems(&self, elems: &[Option<ExprOrSpread>]) -> Option<Vec<String>> {
+    elems
+      .iter()
+      .flatten()
+      .map(|elem| match elem.spread {
+        Some(_) => None,
+        None => self.as_str(&elem.expr),
+      })
+      .collect()
+  }
+
   fn as_reexport(&self, expr: &Expr) -> Option<Reexport> {
     match expr {
-      Expr::Paren(ParenExpr { expr, .. }) => return self.as_reexport(expr),
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_reexport(expr),
+      // require('lib')
+      // require('lib')()
       Expr::Call(call) => {
-        if let Some(path) = with_require_call(&call) {
-          return Some(Reexport { path, extends: vec![] });
-        } else {
-          None
+        if let Some(specifier) = with_require_call(call) {
+          return Some(Reexport::new(specifier));
+        }
+        // _interop_require_wildcard(require('lib'))
+        // _interopRequireWildcard(require('lib'))
+        // _interopNamespaceDefault(require('lib'))
+        // __importStar(require('lib')), __importDefault(require('lib'))
+        let is_namespace_interop = [
+          "_interop_require_wildcard",
+          "_interopRequireWildcard",
+          "_interopNamespace",
+          "_interopNamespaceDefault",
+          "__importStar",
+          "__importDefault",
+        ]
+        .iter()
+        .any(|helper| self.is_runtime_helper_call(call, helper));
+        if is_namespace_interop && !call.args.is_empty() {
+          return self
+            .as_reexport(call.args[0].expr.as_ref())
+            .filter(|reexport| !reexport.called && reexport.member.is_none());
+        }
+        if call.args.is_empty() {
+          if let Some(mut reexport) = with_expr_callee(call).and_then(|callee| self.as_reexport(callee)) {
+            if !reexport.called {
+              reexport.called = true;
+              reexport.extends.clear();
+              return Some(reexport);
+            }
+          }
+        }
+        None
+      }
+      // require('lib').default
+      Expr::Member(MemberExpr { obj, prop, .. }) => {
+        if let Some(mut reexport) = self.as_reexport(obj) {
+          if reexport.member.is_none() {
+            let name = match prop {
+              // require('lib')[key], only if the key is a constant string
+              MemberProp::Computed(ComputedPropName { expr, .. }) => self.as_str(expr),
+              _ => get_prop_name(prop),
+            };
+            if let Some(name) = name {
+              reexport.member = Some(name);
+              reexport.extends.clear();
+              return Some(reexport);
+            }
+          }
         }
+        None
       }
       Expr::Ident(id) => {
         if let Some(value) = self.idents.get(id.sym.as_ref()) {
@@ -282,109 +635,199 @@ impl ModuleLexer {
     }
   }
 
-  fn as_class(&self, expr: &Expr) -> Option<Class> {
+  // require('./foo')
+  // require('./foo').foo
+  // foo_1.foo
+  fn as_named_reexport(&self, expr: &Expr) -> Option<Reexport> {
+    // exports.foo = exports.bar = require('./foo').foo
+    if let Expr::Assign(AssignExpr { right, .. }) = expr {
+      return self.as_named_reexport(right);
+    }
+    let mut reexport = self.as_reexport(expr).filter(|reexport| !reexport.called)?;
+    reexport.extends.clear();
+    Some(reexport)
+  }
+
+  fn as_class(&self, expr: &'a Expr) -> Option<&'a Class> {
     match expr {
-      Expr::Paren(ParenExpr { expr, .. }) => return self.as_class(expr),
-      Expr::Class(ClassExpr { class, .. }) => Some(class.as_ref().clone()),
-      Expr::Ident(id) => {
-        if let Some(value) = self.idents.get(id.sym.as_ref()) {
-          match value {
-            IdentKind::Class(class) => return Some(class.clone()),
-            IdentKind::Alias(id) => return self.as_class(&Expr::Ident(quote_ident(id))),
-            _ => {}
-          }
-        }
-        None
-      }
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_class(expr),
+      Expr::Class(ClassExpr { class, .. }) => Some(class),
+      Expr::Ident(id) => self.as_class_ident(id.sym.as_ref()),
+      _ => None,
+    }
+  }
+
+  fn as_class_ident(&self, name: &str) -> Option<&'a Class> {
+    match self.idents.get(name)? {
+      IdentKind::Class(class) => Some(class),
+      IdentKind::Alias(id) => self.as_class_ident(id),
       _ => None,
     }
   }
 
-  fn as_function(&self, expr: &Expr) -> Option<FnDesc> {
+  fn as_function(&self, expr: &'a Expr) -> Option<FnDesc<'a>> {
     match expr {
-      Expr::Paren(ParenExpr { expr, .. }) => return self.as_function(expr),
-      Expr::Fn(FnExpr { function, .. }) => {
-        if let Function { body: Some(body), .. } = function.as_ref() {
-          Some(FnDesc {
-            stmts: body.stmts.clone(),
-            extends: vec![],
-          })
-        } else {
-          None
-        }
-      }
-      Expr::Ident(id) => {
-        if let Some(value) = self.idents.get(id.sym.as_ref()) {
-          match value {
-            IdentKind::Fn(desc) => return Some(desc.clone()),
-            IdentKind::Alias(id) => return self.as_function(&Expr::Ident(quote_ident(id))),
-            _ => {}
-          }
-        }
-        None
-      }
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_function(expr),
+      Expr::Fn(FnExpr { function, .. }) => function.body.as_ref().map(|body| FnDesc {
+        body: Body::Stmts(&body.stmts),
+        extends: vec![],
+        constructible: is_constructible_function(function),
+      }),
+      Expr::Arrow(arrow) => Some(FnDesc {
+        body: get_arrow_body(arrow),
+        extends: vec![],
+        constructible: false,
+      }),
+      Expr::Ident(id) => self.as_function_ident(id.sym.as_ref()),
+      _ => None,
+    }
+  }
+
+  fn as_function_ident(&self, name: &str) -> Option<FnDesc<'a>> {
+    match self.idents.get(name)? {
+      IdentKind::Fn(desc) => Some(desc.clone()),
+      IdentKind::Alias(id) => self.as_function_ident(id),
       _ => None,
     }
   }
 
-  fn update_exports_from_object(&mut self, obj_props: Vec<PropOrSpread>) {
-    for prop in obj_props {
+  fn update_exports_from_object(&mut self, obj: ObjDesc<'a>, provenance: Provenance) {
+    for prop in obj.props {
       match prop {
         PropOrSpread::Prop(prop) => {
-          let name = match prop.as_ref() {
-            Prop::Shorthand(id) => Some(id.sym.as_ref().to_owned()),
-            Prop::KeyValue(KeyValueProp { key, .. }) => stringify_prop_name(key),
-            Prop::Method(MethodProp { key, .. }) => stringify_prop_name(key),
-            _ => None,
-          };
-          if let Some(name) = name {
-            self.named_exports.insert(name);
+          match prop.as_ref() {
+            // { foo }
+            Prop::Shorthand(id) => {
+              let value = Expr::Ident(id.clone());
+              self.add_export_with_value(id.sym.as_ref().to_owned(), &value, provenance);
+            }
+            // { foo: require('./foo').foo }
+            Prop::KeyValue(KeyValueProp { key, value }) => {
+              if let Some(name) = stringify_prop_name(key) {
+                self.add_export_with_value(name, value, provenance);
+              }
+            }
+            Prop::Method(MethodProp { key, .. }) => {
+              if let Some(name) = stringify_prop_name(key) {
+                self.add_export(name, provenance);
+              }
+            }
+            _ => {}
           }
         }
         PropOrSpread::Spread(SpreadElement { expr, .. }) => match expr.as_ref() {
           Expr::Ident(_) => {
-            if let Some(props) = self.as_obj(expr.as_ref()) {
-              self.update_exports_from_object(props);
+            if let Some(obj) = self.as_obj(expr) {
+              self.update_exports_from_object(obj, provenance);
             }
             if let Some(reexport) = self.as_reexport(expr.as_ref()) {
-              self.reexports.insert(reexport.path);
+              self.add_reexport(reexport, provenance);
             }
           }
           Expr::Call(call) => {
-            if let Some(reexport) = with_require_call(call) {
-              self.reexports.insert(reexport);
+            if let Some(specifier) = with_require_call(call) {
+              self.add_reexport(Reexport::new(specifier), provenance);
             }
           }
           _ => {}
         },
       }
     }
+    for name in obj.extends {
+      self.add_export(name, provenance);
+    }
+    for (name, provenance) in obj.getters {
+      self.add_export(name, provenance);
+    }
+    for reexport in obj.reexports {
+      self.add_reexport(reexport, Provenance::EsbuildReexport);
+    }
+  }
+
+  // { foo: function() { return foo; }, bar: () => _bar.bar }
+  fn update_exports_from_getters(&mut self, obj: ObjDesc<'a>, provenance: Provenance) {
+    for prop in obj.props {
+      if let PropOrSpread::Prop(prop) = prop {
+        let key = match prop.as_ref() {
+          Prop::KeyValue(KeyValueProp { key, .. })
+          | Prop::Method(MethodProp { key, .. })
+          | Prop::Getter(GetterProp { key, .. }) => key,
+          _ => continue,
+        };
+        if let Some(name) = stringify_prop_name(key) {
+          match get_getter_return_value(prop) {
+            Some(value) => self.add_export_with_value(name, value, provenance),
+            None => self.add_export(name, provenance),
+          }
+        }
+      }
+    }
   }
 
   fn eqeq(&self, left: &Expr, right: &Expr) -> bool {
+    self.compare(left, right).unwrap_or(false)
+  }
+
+  // evaluate the expression as a boolean, returns `None` if it's unknown
+  fn as_known_bool(&self, expr: &Expr) -> Option<bool> {
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.as_known_bool(expr),
+      Expr::Unary(UnaryExpr {
+        op: UnaryOp::Bang, arg, ..
+      }) => self.as_known_bool(arg).map(|value| !value),
+      Expr::Bin(BinExpr { op, left, right, .. }) => match op {
+        BinaryOp::EqEq | BinaryOp::EqEqEq => self.compare(left, right),
+        BinaryOp::NotEq | BinaryOp::NotEqEq => self.compare(left, right).map(|eq| !eq),
+        BinaryOp::LogicalAnd => match self.as_known_bool(left)? {
+          true => self.as_known_bool(right),
+          false => Some(false),
+        },
+        BinaryOp::LogicalOr => match self.as_known_bool(left)? {
+          true => Some(true),
+          false => self.as_known_bool(right),
+        },
+        _ => None,
+      },
+      _ => self.as_lit(expr).map(|lit| self.is_true(&Expr::Lit(lit))),
+    }
+  }
+
+  // returns `None` if any side of the comparison is unknown
+  fn compare(&self, left: &Expr, right: &Expr) -> Option<bool> {
     if let Some(left) = self.as_str(left) {
       if let Some(right) = self.as_str(right) {
-        return left == right;
+        return Some(left == right);
       }
     } else if let Some(left) = self.as_num(left) {
       if let Some(right) = self.as_num(right) {
-        return left == right;
+        return Some(left == right);
       }
     } else if let Some(left) = self.as_bool(left) {
       if let Some(right) = self.as_bool(right) {
-        return left == right;
+        return Some(left == right);
       }
     } else if let Some(left) = self.as_null(left) {
       if let Some(right) = self.as_null(right) {
-        return left == right;
+        return Some(left == right);
       }
     }
-    false
+    None
   }
 
   fn is_true(&self, expr: &Expr) -> bool {
+    if let Some(lit) = self.as_define(expr) {
+      return self.is_true(&Expr::Lit(lit.clone()));
+    }
     match expr {
       Expr::Paren(ParenExpr { expr, .. }) => return self.is_true(expr),
+      // !process.env.FOO
+      Expr::Unary(UnaryExpr {
+        op: UnaryOp::Bang, arg, ..
+      }) => {
+        if let Some(value) = self.as_known_bool(arg) {
+          return !value;
+        }
+      }
       Expr::Ident(id) => {
         if let Some(value) = self.idents.get(id.sym.as_ref()) {
           match value {
@@ -432,14 +875,14 @@ impl ModuleLexer {
       if let Some(init) = &decl.init {
         if let Expr::Ident(init_id) = init.as_ref() {
           if init_id.sym.as_ref().eq("exports") {
-            self.exports_alias.insert(id.sym.as_ref().to_owned());
+            self.exports_alias.insert(id.sym.as_ref().to_owned(), ());
           }
         } else if is_member(init, "module", "exports") {
-          self.exports_alias.insert(id.id.sym.as_ref().to_owned());
+          self.exports_alias.insert(id.id.sym.as_ref().to_owned(), ());
         } else if let Expr::Assign(assign) = init.as_ref() {
           if let Some(member) = get_member_expr_from_assign_target(&assign.left) {
-            if is_member(&Expr::Member(member.clone()), "module", "exports") {
-              self.exports_alias.insert(id.id.sym.as_ref().to_owned());
+            if is_member_expr(member, "module", "exports") {
+              self.exports_alias.insert(id.id.sym.as_ref().to_owned(), ());
             }
           }
         }
@@ -448,7 +891,7 @@ impl ModuleLexer {
   }
 
   fn is_exports_ident(&self, id: &str) -> bool {
-    return id.eq("exports") || self.exports_alias.contains(id);
+    id.eq("exports") || self.exports_alias.contains_key(id)
   }
 
   fn is_exports_expr(&self, expr: &Expr) -> bool {
@@ -490,16 +933,16 @@ impl ModuleLexer {
       if bin.op == BinaryOp::LogicalOr {
         if let Some(member_prop_name) = self.get_exports_prop_name(bin.left.as_ref()) {
           if let Expr::Paren(ParenExpr { expr, .. }) = bin.right.as_ref() {
-            if let Expr::Assign(assign) = expr.as_ref() {
-              if let AssignTarget::Simple(expr) = &assign.left {
-                if let SimpleAssignTarget::Member(MemberExpr { obj, prop, .. }) = expr {
-                  if let Expr::Ident(obj) = obj.as_ref() {
-                    if self.is_exports_ident(obj.sym.as_ref()) {
-                      if let Some(prop_name) = get_prop_name(prop) {
-                        if prop_name.eq(&member_prop_name) {
-                          return Some(member_prop_name);
-                        }
-                      }
+            if let Expr::Assign(AssignExpr {
+              left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr { obj, prop, .. })),
+              ..
+            }) = expr.as_ref()
+            {
+              if let Expr::Ident(obj) = obj.as_ref() {
+                if self.is_exports_ident(obj.sym.as_ref()) {
+                  if let Some(prop_name) = get_prop_name(prop) {
+                    if prop_name.eq(&member_prop_name) {
+                      return Some(member_prop_name);
                     }
                   }
                 }
@@ -513,18 +956,23 @@ impl ModuleLexer {
     None
   }
 
-  fn get_exports_from_assign(&mut self, assign: &AssignExpr) {
+  fn get_exports_from_assign(&mut self, assign: &'a AssignExpr) {
     if assign.op == AssignOp::Assign {
       if let AssignTarget::Simple(simple) = &assign.left {
         if let SimpleAssignTarget::Member(MemberExpr { obj, prop, .. }) = &simple {
-          let prop = get_prop_name(&prop);
+          let prop = match prop {
+            // exports[key] = 'bar'
+            MemberProp::Computed(ComputedPropName { expr, .. }) => self.as_str(expr),
+            _ => get_prop_name(prop),
+          };
           if let Some(prop) = prop {
             match obj.as_ref() {
               Expr::Ident(obj) => {
                 let obj_name = obj.sym.as_ref();
                 if self.is_exports_ident(obj_name) {
                   // exports.foo = 'bar'
-                  self.named_exports.insert(prop);
+                  // exports.foo = require('./foo').foo
+                  self.add_export_with_value(prop, &assign.right, Provenance::Assignment);
                   if let Expr::Assign(right_as_assign) = assign.right.as_ref() {
                     self.get_exports_from_assign(right_as_assign);
                   }
@@ -536,17 +984,18 @@ impl ModuleLexer {
                 }
               }
               // module.exports.foo = 'bar'
-              Expr::Member(_) => {
-                if is_member(obj, "module", "exports") {
-                  self.named_exports.insert(prop);
-                  if let Expr::Assign(right_as_assign) = assign.right.as_ref() {
-                    self.get_exports_from_assign(right_as_assign);
-                  }
-                  return;
+              Expr::Member(_) if is_member(obj, "module", "exports") => {
+                self.add_export_with_value(prop, &assign.right, Provenance::Assignment);
+                if let Expr::Assign(right_as_assign) = assign.right.as_ref() {
+                  self.get_exports_from_assign(right_as_assign);
                 }
+                return;
               }
               _ => {}
             }
+          } else if self.is_exports_expr(obj) {
+            self.add_warning(WarningKind::DynamicAssignment, assign.span);
+            return;
           }
         } else if let SimpleAssignTarget::Ident(id) = &simple {
           if self.is_exports_ident(id.sym.as_ref()) {
@@ -557,160 +1006,11 @@ impl ModuleLexer {
         }
       }
       if let Some(name) = self.get_export_name_from_bin_expr(assign.right.as_ref()) {
-        self.named_exports.insert(name);
+        self.add_export(name, Provenance::LogicalAssignment);
       }
     }
   }
 
-  fn get_webpack_exports(&mut self, stmts: &Vec<Stmt>, webpack_require_sym: &str, first_stmt_index: &usize) {
-    stmts.iter().skip(*first_stmt_index).take(8).find(|stmt| match stmt {
-      Stmt::Expr(ExprStmt { expr, .. }) => {
-        if let Expr::Seq(SeqExpr { exprs, .. }) = &**expr {
-          let mut found_webpack_require_exprs = false;
-          for expr in exprs {
-            if let Expr::Call(call) = &**expr {
-              if let Some(Expr::Member(MemberExpr { obj, prop, .. })) = with_expr_callee(call) {
-                if let (Expr::Ident(Ident { sym: obj_sym, .. }), MemberProp::Ident(IdentName { sym: prop_sym, .. })) =
-                  (&**obj, &*prop)
-                {
-                  if !obj_sym.as_ref().eq(webpack_require_sym) {
-                    return false;
-                  }
-                  let prop_sym_ref = prop_sym.as_ref();
-
-                  if prop_sym_ref.eq("r") {
-                    self.named_exports.insert("__esModule".to_string());
-                    found_webpack_require_exprs = true;
-                  }
-                  if prop_sym_ref.eq("d") {
-                    let CallExpr { args, .. } = &*call;
-                    if let Some(ExprOrSpread { expr, .. }) = args.get(1) {
-                      if let Expr::Object(ObjectLit { props, .. }) = &**expr {
-                        for prop in props {
-                          if let PropOrSpread::Prop(prop) = prop {
-                            if let Prop::KeyValue(KeyValueProp {
-                              key: PropName::Ident(IdentName { sym, .. }),
-                              ..
-                            }) = &**prop
-                            {
-                              self.named_exports.insert(sym.as_ref().to_string());
-                              found_webpack_require_exprs = true;
-                            }
-                          }
-                        }
-                      }
-                    }
-                  }
-                }
-              }
-            }
-          }
-          return found_webpack_require_exprs;
-        }
-        return false;
-      }
-      _ => false,
-    });
-  }
-
-  fn get_webpack_require_props_from_props(&mut self, props: &Vec<PropOrSpread>) -> i32 {
-    props
-      .iter()
-      .map(|prop| match prop {
-        PropOrSpread::Prop(prop) => match &**prop {
-          Prop::KeyValue(KeyValueProp {
-            key: PropName::Ident(IdentName { sym, .. }),
-            ..
-          }) => {
-            let sym_ref = sym.as_ref();
-            if sym_ref.eq("r") || sym_ref.eq("d") {
-              return 1;
-            }
-            return 0;
-          }
-          _ => 0,
-        },
-        _ => 0,
-      })
-      .sum()
-  }
-
-  fn get_webpack_require_props_from_stmts(&mut self, stmts: &Vec<Stmt>, webpack_require_sym: &str) -> i32 {
-    return stmts
-      .iter()
-      .map(|stmt| {
-        if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
-          match &**expr {
-            Expr::Seq(SeqExpr { exprs, .. }) => {
-              return exprs
-                .iter()
-                .map(|expr| match &**expr {
-                  Expr::Assign(AssignExpr {
-                    op: AssignOp::Assign,
-                    left,
-                    ..
-                  }) => {
-                    if let AssignTarget::Simple(simple) = &left {
-                      if let SimpleAssignTarget::Member(MemberExpr {
-                        obj,
-                        prop: MemberProp::Ident(IdentName { sym: prop_sym, .. }),
-                        ..
-                      }) = &simple
-                      {
-                        if let Expr::Ident(Ident { sym, .. }) = &**obj {
-                          if sym.as_ref().eq(<str as AsRef<str>>::as_ref(webpack_require_sym)) {
-                            let prop_sym_ref = prop_sym.as_ref();
-                            if prop_sym_ref.eq("r") || prop_sym_ref.eq("d") {
-                              return 1;
-                            }
-                          }
-                        }
-                      }
-                    }
-                    return 0;
-                  }
-                  Expr::Call(call) => {
-                    if let Some(body) = get_iife_body(call) {
-                      return self.get_webpack_require_props_from_stmts(&body, webpack_require_sym);
-                    }
-                    return 0;
-                  }
-                  _ => 0,
-                })
-                .sum();
-            }
-            Expr::Assign(AssignExpr {
-              op: AssignOp::Assign,
-              left,
-              ..
-            }) => {
-              if let AssignTarget::Simple(simple) = &left {
-                if let SimpleAssignTarget::Member(MemberExpr {
-                  obj,
-                  prop: MemberProp::Ident(IdentName { sym: prop_sym, .. }),
-                  ..
-                }) = &simple
-                {
-                  if let Expr::Ident(Ident { sym, .. }) = &**obj {
-                    if sym.as_ref().eq(<str as AsRef<str>>::as_ref(webpack_require_sym)) {
-                      let prop_sym_ref = prop_sym.as_ref();
-                      if prop_sym_ref.eq("r") || prop_sym_ref.eq("d") {
-                        return 1;
-                      }
-                    }
-                  }
-                }
-              }
-              return 0;
-            }
-            _ => 0,
-          };
-        }
-        return 0;
-      })
-      .sum();
-  }
-
   // function (e, t, r) {
   //   "use strict";
   //   r.r(t), r.d(t, "named", (function () { return n }));
@@ -718,36 +1018,35 @@ impl ModuleLexer {
   //   t.default = "default-export";
   // }
   fn get_webpack4_exports(&mut self, expr: &Expr, webpack_exports_sym: &str, webpack_require_sym: Option<&str>) {
-    match &*expr {
+    match expr {
       Expr::Seq(SeqExpr { exprs, .. }) => {
         for expr in exprs {
-          self.get_webpack4_exports(&expr, webpack_exports_sym, webpack_require_sym)
+          self.get_webpack4_exports(expr, webpack_exports_sym, webpack_require_sym)
         }
       }
-      Expr::Call(call) => match webpack_require_sym {
-        Some(webpack_require_sym) => {
+      Expr::Call(call) => {
+        if let Some(webpack_require_sym) = webpack_require_sym {
           if let Some(Expr::Member(MemberExpr { obj, prop, .. })) = with_expr_callee(call) {
             if let (Expr::Ident(Ident { sym: obj_sym, .. }), MemberProp::Ident(IdentName { sym: prop_sym, .. })) =
-              (&**obj, &*prop)
+              (&**obj, prop)
             {
               if obj_sym.as_ref().eq(webpack_require_sym) && prop_sym.as_ref().eq("r") {
-                self.named_exports.insert("__esModule".to_string());
+                self.add_export("__esModule".to_string(), Provenance::WebpackNamespace);
               }
               if obj_sym.as_ref().eq(webpack_require_sym) && prop_sym.as_ref().eq("d") {
-                let CallExpr { args, .. } = &*call;
+                let CallExpr { args, .. } = call;
                 if let Some(ExprOrSpread { expr, .. }) = args.get(1) {
                   if let Expr::Lit(Lit::Str(Str { value, .. })) = &**expr {
-                    self.named_exports.insert(value.to_string_lossy().into_owned());
+                    self.add_export(value.to_string_lossy().into_owned(), Provenance::WebpackDefine);
                   }
                 }
               }
             }
           }
         }
-        None => {}
-      },
+      }
       Expr::Assign(AssignExpr {
-        left,
+        left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr { obj, prop, .. })),
         op: AssignOp::Assign,
         ..
       }) => {
@@ -767,15 +1066,11 @@ impl ModuleLexer {
         // This doesn't feel right but is what ends up matching
         // t.default = "default-export"
         // May be an swc ast bug
-        if let AssignTarget::Simple(simple) = &left {
-          if let SimpleAssignTarget::Member(MemberExpr { obj, prop, .. }) = &simple {
-            if let Expr::Ident(Ident { sym, .. }) = &**obj {
-              if sym.as_ref().eq(webpack_exports_sym) {
-                if let MemberProp::Ident(prop) = prop {
-                  if prop.sym.as_ref().eq("default") {
-                    self.named_exports.insert("default".to_string());
-                  }
-                }
+        if let Expr::Ident(Ident { sym, .. }) = &**obj {
+          if sym.as_ref().eq(webpack_exports_sym) {
+            if let MemberProp::Ident(prop) = prop {
+              if prop.sym.as_ref().eq("default") {
+                self.add_export("default".to_string(), Provenance::WebpackDefault);
               }
             }
           }
@@ -785,21 +1080,314 @@ impl ModuleLexer {
     }
   }
 
-  fn is_umd_iife_call(&mut self, call: &CallExpr) -> Option<Vec<Stmt>> {
+  // Object.keys(_foo).forEach(function (key) {
+  //   if (key === "default" || key === "__esModule") return;
+  //   if (Object.prototype.hasOwnProperty.call(_exportNames, key)) return;
+  //   Object.defineProperty(exports, key, { enumerable: true, get: function () { return _foo[key]; } });
+  // })
+  fn as_export_star_loop(&self, call: &CallExpr) -> Option<Reexport> {
+    let Expr::Member(MemberExpr { obj, prop, .. }) = with_expr_callee(call)? else {
+      return None;
+    };
+    if get_prop_name(prop).as_deref() != Some("forEach") || call.args.is_empty() {
+      return None;
+    }
+    let Expr::Call(keys_call) = obj.as_ref() else {
+      return None;
+    };
+    if !is_object_static_mothod_call(keys_call, "keys") || keys_call.args.is_empty() {
+      return None;
+    }
+    let (param, body) = match call.args[0].expr.as_ref() {
+      Expr::Fn(FnExpr { function, .. }) => (
+        function.params.first().map(|param| &param.pat)?,
+        Body::Stmts(&function.body.as_ref()?.stmts),
+      ),
+      Expr::Arrow(arrow) => (arrow.params.first()?, get_arrow_body(arrow)),
+      _ => return None,
+    };
+    let Pat::Ident(key) = param else {
+      return None;
+    };
+    if !self.is_export_key_body(body, key.sym.as_ref()) {
+      return None;
+    }
+    self
+      .as_reexport(keys_call.args[0].expr.as_ref())
+      .filter(|reexport| !reexport.called && reexport.member.is_none())
+  }
+
+  // check if the statement exports the key, e.g. `if (key !== "default") exports[key] = _foo[key]`
+  fn is_export_key_stmt(&self, stmt: &Stmt, key: &str) -> bool {
+    match stmt {
+      Stmt::Expr(ExprStmt { expr, .. }) => self.is_export_key_expr(expr, key),
+      // `if (key === "foo") exports[key] = ...` only exports some of the keys
+      Stmt::If(IfStmt { test, cons, .. }) => !is_key_equality(test, key) && self.is_export_key_stmt(cons, key),
+      Stmt::Block(BlockStmt { stmts, .. }) => stmts.iter().any(|stmt| self.is_export_key_stmt(stmt, key)),
+      _ => false,
+    }
+  }
+
+  // Object.defineProperty(exports, 'foo', { value: ... })
+  // Object.defineProperty(module, 'exports', { value: ... })
+  fn define_export_property(
+    &mut self,
+    target: &'a Expr,
+    name: Option<String>,
+    desc: &'a Expr,
+    span: Span,
+    provenance: Provenance,
+  ) {
+    let is_module = is_module_ident(target);
+    let is_exports = self.is_exports_expr(target);
+    let mut with_value_or_getter = false;
+    let mut with_value: Option<&'a Expr> = None;
+    let mut with_getter: Option<&'a Expr> = None;
+    if let Some(desc_obj) = self.as_obj(desc) {
+      for prop in desc_obj.props {
+        if let PropOrSpread::Prop(prop) = prop {
+          let key = match prop.as_ref() {
+            Prop::KeyValue(KeyValueProp { key, value, .. }) => {
+              let key = stringify_prop_name(key);
+              if let Some(key) = &key {
+                if key.eq("value") {
+                  with_value = Some(value);
+                }
+              }
+              key
+            }
+            Prop::Method(MethodProp { key, .. }) => stringify_prop_name(key),
+            _ => None,
+          };
+          if let Some(key) = key {
+            if key.eq("value") || key.eq("get") {
+              with_value_or_getter = true;
+              if key.eq("get") {
+                with_getter = get_getter_return_value(prop);
+              }
+              break;
+            }
+          }
+        }
+      }
+      // var desc = {}; desc.value = ...
+      if !with_value_or_getter {
+        if let Some(key) = desc_obj.extends.iter().find(|key| *key == "value" || *key == "get") {
+          with_value_or_getter = true;
+          if key.eq("value") {
+            with_value = Some(&ASSIGNED_PROP_VALUE);
+          }
+        }
+      }
+    }
+    if is_exports {
+      // Object.defineProperty(exports, 'foo', { get: function () { return require('./foo').foo } })
+      let reexport = with_getter.and_then(|getter| self.as_named_reexport(getter));
+      match (name, reexport) {
+        (Some(name), Some(reexport)) => self.add_named_reexport(name, reexport, provenance),
+        (Some(name), None) if with_value_or_getter => self.add_export(name, provenance),
+        (None, _) => self.add_warning(WarningKind::DynamicDefineProperty, span),
+        _ => {}
+      }
+    }
+    if is_module {
+      if let Some(expr) = with_value {
+        self.replace_exports_from_expr(expr);
+      }
+    }
+  }
+
+  // exports[key] = _foo[key]
+  // Object.defineProperty(exports, key, { ... })
+  // __createBinding(exports, _foo, key)
+  fn is_export_key_expr(&self, expr: &Expr, key: &str) -> bool {
+    let is_key = |expr: &Expr| matches!(expr, Expr::Ident(id) if id.sym.as_ref().eq(key));
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.is_export_key_expr(expr, key),
+      Expr::Assign(AssignExpr {
+        left:
+          AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Computed(ComputedPropName { expr, .. }),
+            ..
+          })),
+        ..
+      }) => self.is_exports_expr(obj) && is_key(expr),
+      Expr::Call(call) if is_define_property_call(call) => {
+        call.args.len() >= 3 && self.is_exports_expr(&call.args[0].expr) && is_key(&call.args[1].expr)
+      }
+      // __createBinding(exports, m, key)
+      Expr::Call(call) => {
+        self.is_runtime_helper_call(call, "__createBinding")
+          && call.args.len() >= 3
+          && self.is_exports_expr(&call.args[0].expr)
+          && is_key(&call.args[2].expr)
+      }
+      _ => false,
+    }
+  }
+
+  // match:
+  // require("tslib").__exportStar(..., exports)
+  // (0, require("tslib").__exportStar)(..., exports)
+  // const tslib = require("tslib"); (0, tslib.__exportStar)(..., exports)
+  // const {__exportStar} = require("tslib"); (0, __exportStar)(..., exports)
+  // const { __exportStar: exportStar } = require("tslib"); (0, exportStar)(..., exports)
+  // const __exportStar = () => {}; __exportStar(..., exports)
+  // var exportStar = (this && this.__exportStar) || function (m, exports) { ... }; exportStar(..., exports)
+  fn is_runtime_helper_call(&self, call: &CallExpr, helper: &str) -> bool {
+    match with_expr_callee(call) {
+      Some(Expr::Paren(ParenExpr { expr, .. })) => match expr.as_ref() {
+        Expr::Seq(SeqExpr { exprs, .. }) => exprs.last().is_some_and(|last| self.is_runtime_helper(last, helper)),
+        expr => self.is_runtime_helper(expr, helper),
+      },
+      Some(callee) => self.is_runtime_helper(callee, helper),
+      None => false,
+    }
+  }
+
+  // match:
+  // __exportStar
+  // tslib.__exportStar
+  // _export_star._, the default export of `@swc/helpers/_/_export_star`
+  // exportStar, a local name that is bound to the helper
+  fn is_runtime_helper(&self, expr: &Expr, helper: &str) -> bool {
+    match expr {
+      Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(prop),
+        ..
+      }) => prop.sym.as_ref().eq(helper) || (prop.sym.as_ref().eq("_") && self.is_runtime_helper(obj, helper)),
+      Expr::Ident(id) => {
+        let name = id.sym.as_ref();
+        name.eq(helper)
+          || match self.idents.get(name) {
+            Some(IdentKind::Alias(alias)) => self.is_runtime_helper(&Expr::Ident(quote_ident(alias)), helper),
+            Some(IdentKind::Reexport(reexport)) => !reexport.called && reexport.member.as_deref() == Some(helper),
+            _ => false,
+          }
+      }
+      _ => false,
+    }
+  }
+
+  // for (var p in m) if (p !== "default" && !Object.prototype.hasOwnProperty.call(exports, p)) __createBinding(exports, m, p);
+  // for (var p in m) if (!exports.hasOwnProperty(p)) exports[p] = m[p];
+  fn as_export_star_for_in(&self, stmt: &ForInStmt) -> Option<Reexport> {
+    let key = get_for_in_key(stmt)?;
+    if !self.is_export_key_stmt(&stmt.body, key.sym.as_ref()) {
+      return None;
+    }
+    self
+      .as_reexport(&stmt.right)
+      .filter(|reexport| !reexport.called && reexport.member.is_none())
+  }
+
+  // get the statically known keys of a loop, the loop variable and the loop body:
+  // for (const key of ["foo", "bar"]) { ... }
+  // for (var key in { foo: 1, bar: 2 }) { ... }
+  fn as_const_loop_stmt(&self, stmt: &'a Stmt) -> Option<(Vec<String>, &'a str, Body<'a>)> {
+    let (left, keys, body) = match stmt {
+      Stmt::ForOf(ForOfStmt {
+        is_await: false,
+        left,
+        right,
+        body,
+        ..
+      }) => (left, self.as_str_array(right)?, body),
+      Stmt::ForIn(ForInStmt { left, right, body, .. }) => (left, get_obj_keys(&self.as_obj(right)?), body),
+      _ => return None,
+    };
+    let key = get_for_head_ident(left)?;
+    Some((keys, key.sym.as_ref(), Body::Stmts(std::slice::from_ref(body.as_ref()))))
+  }
+
+  // ["foo", "bar"].forEach(function (key) { ... })
+  fn as_const_loop_call(&self, call: &'a CallExpr) -> Option<(Vec<String>, &'a str, Body<'a>)> {
+    let Expr::Member(MemberExpr { obj, prop, .. }) = with_expr_callee(call)? else {
+      return None;
+    };
+    if get_prop_name(prop).as_deref() != Some("forEach") || call.args.is_empty() {
+      return None;
+    }
+    let keys = self.as_str_array(obj)?;
+    let (params, body) = get_fn_params_and_body(&call.args[0].expr)?;
+    let Some(Pat::Ident(BindingIdent { id: key, .. })) = params.first() else {
+      return None;
+    };
+    Some((keys, key.sym.as_ref(), body))
+  }
+
+  // check if the loop body exports the key, e.g. `exports[key] = make(key)`
+  fn is_export_key_body(&self, body: Body, key: &str) -> bool {
+    match body {
+      Body::Stmts(stmts) => stmts.iter().any(|stmt| self.is_export_key_stmt(stmt, key)),
+      Body::Expr(expr) => self.is_export_key_expr(expr, key),
+    }
+  }
+
+  fn add_loop_exports(&mut self, (keys, key, body): (Vec<String>, &str, Body)) {
+    if self.is_export_key_body(body, key) {
+      for name in keys {
+        self.add_export(name, Provenance::LoopAssignment);
+      }
+    }
+  }
+
+  // var api = {}; ["get", "post"].forEach(function (method) { api[method] = ... })
+  fn mark_loop_props(&mut self, (keys, key, body): (Vec<String>, &str, Body)) {
+    let target = match body {
+      Body::Stmts(stmts) => stmts.iter().find_map(|stmt| get_key_assign_target(stmt, key)),
+      Body::Expr(expr) => get_key_assign_target_from_expr(expr, key),
+    };
+    if let Some(target) = target {
+      for key in keys {
+        self.mark_ident_prop(&target, key);
+      }
+    }
+  }
+
+  // var foo = {}
+  // foo.bar = 'bar'
+  fn mark_ident_prop(&mut self, name: &str, key: String) {
+    if let Some(mut desc) = self.as_obj_ident(name) {
+      desc.extends.push(key);
+      self.idents.insert(name.into(), IdentKind::Object(desc));
+    } else if let Some(mut desc) = self.as_function_ident(name) {
+      desc.extends.push(key);
+      self.idents.insert(name.into(), IdentKind::Fn(desc));
+    } else if let Some(mut reexport) = self.as_reexport(&Expr::Ident(quote_ident(name))) {
+      reexport.extends.push(key);
+      self.idents.insert(name.into(), IdentKind::Reexport(reexport));
+    }
+  }
+
+  // var __webpack_export_target__ = exports;
+  // for (var __webpack_i__ in __webpack_exports__) __webpack_export_target__[__webpack_i__] = __webpack_exports__[__webpack_i__];
+  fn as_export_copy_for_in(&self, stmt: &ForInStmt) -> Option<ObjDesc<'a>> {
+    let key = get_for_in_key(stmt)?;
+    if !self.is_export_key_stmt(&stmt.body, key.sym.as_ref()) {
+      return None;
+    }
+    match stmt.right.as_ref() {
+      Expr::Ident(id) => self.as_obj_ident(id.sym.as_ref()),
+      _ => None,
+    }
+  }
+
+  fn is_umd_iife_call(&mut self, call: &'a CallExpr) -> Option<Body<'a>> {
     if call.args.len() == 2 {
-      let mut arg1 = call.args.get(1).unwrap().expr.as_ref();
+      let mut arg1 = call.args[1].expr.as_ref();
       if let Expr::Paren(ParenExpr { expr, .. }) = arg1 {
         arg1 = expr.as_ref();
       }
-      let stmts = match arg1 {
-        Expr::Fn(func) => {
-          if let Some(BlockStmt { stmts, .. }) = &func.function.body {
-            Some(stmts.clone())
-          } else {
-            None
-          }
-        }
-        Expr::Arrow(arrow) => Some(get_arrow_body_as_stmts(&arrow)),
+      let body = match arg1 {
+        Expr::Fn(func) => func
+          .function
+          .body
+          .as_ref()
+          .map(|BlockStmt { stmts, .. }| Body::Stmts(stmts)),
+        Expr::Arrow(arrow) => Some(get_arrow_body(arrow)),
         _ => None,
       };
       let expr = if let Some(callee) = with_expr_callee(call) {
@@ -812,8 +1400,8 @@ impl ModuleLexer {
       };
       match expr {
         Expr::Fn(func) => {
-          if is_umd_params(&func.function.params.iter().map(|p| p.pat.clone()).collect()) {
-            return stmts;
+          if is_umd_params(func.function.params.iter().map(|p| &p.pat)) {
+            return body;
           } else if let Some(BlockStmt { stmts: body_stmts, .. }) = &func.function.body {
             if is_umd_checks(body_stmts) {
               if let Some(Param {
@@ -821,22 +1409,21 @@ impl ModuleLexer {
                   id: Ident { sym, .. }, ..
                 }),
                 ..
-              }) = &func.function.params.get(0)
+              }) = &func.function.params.first()
               {
-                self.exports_alias.insert(sym.as_ref().to_owned());
+                self.exports_alias.insert(sym.as_ref().to_owned(), ());
               }
 
-              return stmts;
+              return body;
             }
             return None;
           }
         }
-        Expr::Arrow(arrow) => {
+        Expr::Arrow(arrow)
           // TODO: detect for minified umd, haven't seen any in the wild using arrow fns yet though
-          if is_umd_params(&arrow.params) {
-            return stmts;
+          if is_umd_params(arrow.params.iter()) => {
+            return body;
           }
-        }
         _ => {}
       }
     }
@@ -844,7 +1431,7 @@ impl ModuleLexer {
   }
 
   // walk and mark idents
-  fn walk_stmts(&mut self, stmts: &Vec<Stmt>) -> bool {
+  fn walk_stmts(&mut self, stmts: impl IntoIterator<Item = &'a Stmt>) -> bool {
     for stmt in stmts {
       match stmt {
         Stmt::Decl(decl) => match decl {
@@ -860,35 +1447,31 @@ impl ModuleLexer {
                     self.idents.insert(id.into(), IdentKind::Unkonwn);
                   }
                 }
+                // const { NODE_ENV } = process.env
+                // const { NODE_ENV: env } = process.env
+                // const { __exportStar: exportStar } = require("tslib")
                 Pat::Object(ObjectPat { props, .. }) => {
-                  let mut process_env_init = false;
-                  if let Some(init) = &decl.init {
-                    process_env_init = is_member(init.as_ref(), "process", "env");
+                  let init_key = match &decl.init {
+                    Some(init) => get_define_key(init),
+                    None => None,
                   };
-                  if process_env_init {
-                    for prop in props {
-                      match prop {
-                        ObjectPatProp::Assign(AssignPatProp { key, .. }) => {
-                          let key = key.sym.as_ref();
-                          if key.eq("NODE_ENV") {
-                            self.idents.insert(
-                              key.to_owned(),
-                              IdentKind::Lit(Lit::Str(quote_str(self.node_env.as_str()))),
-                            );
-                          }
-                        }
-                        ObjectPatProp::KeyValue(KeyValuePatProp { key, value, .. }) => {
-                          let key = stringify_prop_name(&key);
-                          if let (Some(key), Pat::Ident(rename)) = (key, value.as_ref()) {
-                            if key.eq("NODE_ENV") {
-                              self.idents.insert(
-                                rename.id.sym.as_ref().to_owned(),
-                                IdentKind::Lit(Lit::Str(quote_str(self.node_env.as_str()))),
-                              );
-                            }
-                          }
-                        }
-                        _ => {}
+                  let init_reexport = match &decl.init {
+                    Some(init) => self
+                      .as_reexport(init)
+                      .filter(|reexport| !reexport.called && reexport.member.is_none()),
+                    None => None,
+                  };
+                  for prop in props {
+                    let Some((key, name)) = get_obj_pat_prop_binding(prop) else {
+                      continue;
+                    };
+                    if let Some(reexport) = &init_reexport {
+                      let mut reexport = Reexport::new(reexport.specifier.clone());
+                      reexport.member = Some(key);
+                      self.idents.insert(name, IdentKind::Reexport(reexport));
+                    } else if let Some(init_key) = &init_key {
+                      if let Some(lit) = self.lookup_define(&format!("{}.{}", init_key, key)) {
+                        self.idents.insert(name, IdentKind::Lit(lit.clone()));
                       }
                     }
                   }
@@ -898,80 +1481,21 @@ impl ModuleLexer {
             }
           }
           Decl::Fn(FnDecl { ident, function, .. }) => {
-            self.mark_ident(
-              ident.sym.as_ref(),
-              &Expr::Fn(FnExpr {
-                ident: Some(ident.clone()),
-                function: function.clone(),
-              }),
-            );
+            self.mark_function(ident.sym.as_ref(), function);
           }
           Decl::Class(ClassDecl { ident, class, .. }) => {
-            self.mark_ident(
-              ident.sym.as_ref(),
-              &Expr::Class(ClassExpr {
-                ident: Some(ident.clone()),
-                class: class.clone(),
-              }),
-            );
+            self.idents.insert(ident.sym.as_ref().into(), IdentKind::Class(class));
           }
           _ => {}
         },
-        Stmt::Expr(ExprStmt { expr, .. }) => {
-          match expr.as_ref() {
-            Expr::Assign(assign) => {
-              if assign.op == AssignOp::Assign {
-                match &assign.left {
-                  AssignTarget::Simple(simple) => match simple {
-                    // var foo = 'boo'
-                    // foo = 'bar'
-                    SimpleAssignTarget::Ident(BindingIdent { id, .. }) => {
-                      let id = id.sym.as_ref();
-                      if self.idents.contains_key(id) {
-                        self.mark_ident(id, &assign.right.as_ref())
-                      }
-                    }
-                    // var foo = {}
-                    // foo.bar = 'bar'
-                    SimpleAssignTarget::Member(MemberExpr { obj, prop, .. }) => {
-                      let key = get_prop_name(&prop);
-                      if let Some(key) = key {
-                        if let Expr::Ident(obj_id) = obj.as_ref() {
-                          let obj_name = obj_id.sym.as_ref();
-                          if let Some(mut props) = self.as_obj(&obj) {
-                            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                              key: PropName::Ident(IdentName::from(key)),
-                              value: Box::new(Expr::Lit(Lit::Bool(Bool {
-                                span: DUMMY_SP,
-                                value: true,
-                              }))),
-                            }))));
-                            self.idents.insert(obj_name.into(), IdentKind::Object(props));
-                          } else if let Some(FnDesc { stmts, mut extends }) = self.as_function(&obj) {
-                            extends.push(key.to_owned());
-                            self
-                              .idents
-                              .insert(obj_name.into(), IdentKind::Fn(FnDesc { stmts, extends }));
-                          } else if let Some(Reexport { path, mut extends }) = self.as_reexport(&obj) {
-                            extends.push(key.to_owned());
-                            self
-                              .idents
-                              .insert(obj_name.into(), IdentKind::Reexport(Reexport { path, extends }));
-                          }
-                        }
-                      }
-                    }
-                    _ => {}
-                  },
-                  _ => {}
-                };
-              }
-            }
-            _ => {}
-          };
+        Stmt::Expr(ExprStmt { expr, .. }) => self.mark_idents_from_expr(expr),
+        Stmt::ForOf(_) | Stmt::ForIn(_) => {
+          if let Some(const_loop) = self.as_const_loop_stmt(stmt) {
+            self.mark_loop_props(const_loop);
+          }
         }
         Stmt::Block(BlockStmt { stmts, .. }) => {
-          let returned = self.walk_stmts(&stmts);
+          let returned = self.walk_stmts(stmts);
           if returned {
             return true;
           }
@@ -979,11 +1503,12 @@ impl ModuleLexer {
         Stmt::If(IfStmt { test, cons, alt, .. }) => {
           let mut returned = false;
           if self.is_true(test) {
-            returned = self.walk_stmts(&vec![cons.as_ref().clone()])
+            returned = self.walk_stmts([cons.as_ref()])
           } else if let Some(alt) = alt {
-            returned = self.walk_stmts(&vec![alt.as_ref().clone()])
+            returned = self.walk_stmts([alt.as_ref()])
           }
-          if returned {
+          // only an `if` with a known condition returns unconditionally
+          if returned && self.as_known_bool(test).is_some() {
             return true;
           }
         }
@@ -994,7 +1519,74 @@ impl ModuleLexer {
     false
   }
 
-  fn parse_expr(&mut self, expr: &Expr) {
+  fn mark_idents_from_expr(&mut self, expr: &'a Expr) {
+    if let Expr::Assign(AssignExpr {
+      op: AssignOp::Assign,
+      left: AssignTarget::Simple(simple),
+      right,
+      ..
+    }) = expr
+    {
+      match simple {
+        // var foo = 'boo'
+        // foo = 'bar'
+        SimpleAssignTarget::Ident(BindingIdent { id, .. }) => {
+          let id = id.sym.as_ref();
+          if self.idents.contains_key(id) {
+            self.mark_ident(id, right)
+          }
+        }
+        // var foo = {}
+        // foo.bar = 'bar'
+        SimpleAssignTarget::Member(MemberExpr { obj, prop, .. }) => {
+          if let (Some(key), Expr::Ident(obj_id)) = (get_prop_name(prop), obj.as_ref()) {
+            self.mark_ident_prop(obj_id.sym.as_ref(), key);
+          }
+        }
+        _ => {}
+      }
+    } else if let Expr::Seq(SeqExpr { exprs, .. }) = expr {
+      // e.r(t), e.d(t, { foo: () => o })
+      for expr in exprs {
+        self.mark_idents_from_expr(expr);
+      }
+    } else if let Expr::Call(call) = expr {
+      // var src_exports = {}
+      // __export(src_exports, { foo: () => foo })
+      // __reExport(src_exports, require("foo"), module.exports)
+      // var __webpack_exports__ = {}
+      // __webpack_require__.d(__webpack_exports__, { foo: () => foo })
+      if call.args.is_empty() || call.args.iter().any(|arg| arg.spread.is_some()) {
+        return;
+      }
+      if let Some(const_loop) = self.as_const_loop_call(call) {
+        self.mark_loop_props(const_loop);
+        return;
+      }
+      if let (Expr::Ident(obj_id), Some(mut desc)) = (call.args[0].expr.as_ref(), self.as_obj(&call.args[0].expr)) {
+        let webpack_getters = get_webpack_getters(call, self.webpack_runtimes);
+        if !webpack_getters.is_empty() {
+          desc.getters.extend(webpack_getters);
+        } else if is_helper_call(call, "__export") && call.args.len() >= 2 {
+          if let Some(all) = self.as_obj(&call.args[1].expr) {
+            let names = get_obj_prop_names(all.props);
+            desc
+              .getters
+              .extend(names.into_iter().map(|name| (name, Provenance::EsbuildExport)));
+          }
+        } else if is_helper_call(call, "__reExport") && call.args.len() >= 2 {
+          if let Some(reexport) = self.as_reexport(&call.args[1].expr) {
+            desc.reexports.push(reexport);
+          }
+        } else {
+          return;
+        }
+        self.idents.insert(obj_id.sym.as_ref().into(), IdentKind::Object(desc));
+      }
+    }
+  }
+
+  fn parse_expr(&mut self, expr: &'a Expr) {
     match expr {
       Expr::Seq(SeqExpr { exprs, .. }) => {
         for expr in exprs {
@@ -1009,7 +1601,7 @@ impl ModuleLexer {
       // exports = module.exports = { foo: 'bar' }
       // foo = exports.foo || (exports.foo = {})
       Expr::Assign(assign) => {
-        self.get_exports_from_assign(&assign);
+        self.get_exports_from_assign(assign);
       }
       // Object.defineProperty(exports, 'foo', { value: 'bar' })
       // Object.defineProperty((0, exports), 'foo', { value: 'bar' })
@@ -1025,155 +1617,245 @@ impl ModuleLexer {
       // tslib.__exportStar(..., exports)
       // __exportStar(..., exports)
       Expr::Call(call) => {
-        if is_object_static_mothod_call(&call, "defineProperty") && call.args.len() >= 3 {
-          let arg0 = &call.args[0];
-          let arg1 = &call.args[1];
-          let arg2 = &call.args[2];
-          let is_module = is_module_ident(arg0.expr.as_ref());
-          let is_exports = self.is_exports_expr(arg0.expr.as_ref());
-
-          let name = self.as_str(arg1.expr.as_ref());
-          let mut with_value_or_getter = false;
-          let mut with_value: Option<Expr> = None;
-          if let Some(props) = self.as_obj(arg2.expr.as_ref()) {
-            for prop in props {
-              if let PropOrSpread::Prop(prop) = prop {
-                let key = match prop.as_ref() {
-                  Prop::KeyValue(KeyValueProp { key, value, .. }) => {
-                    let key = stringify_prop_name(key);
-                    if let Some(key) = &key {
-                      if key.eq("value") {
-                        with_value = Some(value.as_ref().clone());
-                      }
+        if is_define_property_call(call) && call.args.len() >= 3 {
+          let name = self.as_str(call.args[1].expr.as_ref());
+          self.define_export_property(
+            &call.args[0].expr,
+            name,
+            &call.args[2].expr,
+            call.span,
+            Provenance::DefineProperty,
+          );
+        } else if is_object_static_mothod_call(call, "defineProperties") && call.args.len() >= 2 {
+          let target = call.args[0].expr.as_ref();
+          let is_module = is_module_ident(target);
+          match self.as_obj(&call.args[1].expr) {
+            Some(descs) => {
+              for prop in descs.props {
+                if let PropOrSpread::Prop(prop) = prop {
+                  if let Prop::KeyValue(KeyValueProp { key, value }) = prop.as_ref() {
+                    let name = stringify_prop_name(key);
+                    // Object.defineProperties(module, { exports: { value: ... } })
+                    if is_module && name.as_deref() != Some("exports") {
+                      continue;
                     }
-                    key
-                  }
-                  Prop::Method(MethodProp { key, .. }) => stringify_prop_name(key),
-                  _ => None,
-                };
-                if let Some(key) = key {
-                  if key.eq("value") || key.eq("get") {
-                    with_value_or_getter = true;
-                    break;
+                    self.define_export_property(target, name, value, call.span, Provenance::DefineProperties);
                   }
                 }
               }
             }
+            None if self.is_exports_expr(target) => self.add_warning(WarningKind::DynamicDefineProperty, call.span),
+            None => {}
           }
-          if is_exports && with_value_or_getter {
-            if let Some(name) = name {
-              self.named_exports.insert(name);
-            }
-          }
-          if is_module {
-            if let Some(expr) = with_value {
-              self.replace_exports_from_expr(&expr);
+        } else if is_object_static_mothod_call(call, "setPrototypeOf") && call.args.len() >= 2 {
+          // Object.setPrototypeOf(module.exports, require('./base'))
+          if self.is_exports_expr(call.args[0].expr.as_ref()) {
+            if let Some(reexport) = self.as_reexport(&call.args[1].expr) {
+              self.add_reexport(reexport, Provenance::SetPrototypeOf);
+            } else if let Some(desc) = self.as_obj(&call.args[1].expr) {
+              self.update_exports_from_object(desc, Provenance::SetPrototypeOf);
             }
           }
-        } else if is_object_static_mothod_call(&call, "assign") && call.args.len() >= 2 {
+        } else if is_object_static_mothod_call(call, "assign") && call.args.len() >= 2 {
           let is_module = is_module_ident(call.args[0].expr.as_ref());
           let is_exports = self.is_exports_expr(call.args[0].expr.as_ref());
           for arg in &call.args[1..] {
-            if let Some(props) = self.as_obj(&arg.expr) {
+            if let Some(desc) = self.as_obj(&arg.expr) {
               if is_module {
-                let mut with_exports: Option<Expr> = None;
-                for prop in props {
+                let mut with_exports: Option<&'a Expr> = None;
+                for prop in desc.props {
                   if let PropOrSpread::Prop(prop) = prop {
                     if let Prop::KeyValue(KeyValueProp { key, value, .. }) = prop.as_ref() {
                       let key = stringify_prop_name(key);
                       if let Some(key) = &key {
                         if self.is_exports_ident(key) {
-                          with_exports = Some(value.as_ref().clone());
+                          with_exports = Some(value);
                           break;
                         }
                       }
                     };
                   }
                 }
+                // var obj = {}; obj.exports = ...
+                if with_exports.is_none() && desc.extends.iter().any(|key| self.is_exports_ident(key)) {
+                  with_exports = Some(&ASSIGNED_PROP_VALUE);
+                }
                 if let Some(exports_expr) = with_exports {
-                  self.replace_exports_from_expr(&exports_expr);
+                  self.replace_exports_from_expr(exports_expr);
                 }
               } else if is_exports {
-                self.update_exports_from_object(props);
+                self.update_exports_from_object(desc, Provenance::ObjectAssign);
               }
             } else if let Some(reexport) = self.as_reexport(&arg.expr) {
               if is_exports {
-                self.reexports.insert(reexport.path);
+                self.add_reexport(reexport, Provenance::ObjectAssign);
+              }
+            }
+          }
+        } else if self.is_runtime_helper_call(call, "__createBinding") && call.args.len() >= 3 {
+          // __createBinding(exports, require('./foo'), 'foo')
+          // __createBinding(exports, foo_1, 'default', 'foo')
+          let is_exports = self.is_exports_expr(call.args[0].expr.as_ref());
+          let member = self.as_str(call.args[2].expr.as_ref());
+          let name = match call.args.get(3) {
+            Some(arg) => self.as_str(arg.expr.as_ref()),
+            None => member.clone(),
+          };
+          if let (true, Some(member), Some(name)) = (is_exports, member, name) {
+            match self.as_reexport(call.args[1].expr.as_ref()) {
+              Some(mut reexport) if !reexport.called && reexport.member.is_none() => {
+                reexport.member = Some(member);
+                reexport.extends.clear();
+                self.add_named_reexport(name, reexport, Provenance::CreateBinding);
               }
+              _ => self.add_export(name, Provenance::CreateBinding),
             }
           }
-        } else if is_tslib_export_star_call(&call) && call.args.len() >= 2 {
+        } else if self.is_runtime_helper_call(call, "__exportStar") && call.args.len() >= 2 {
           let is_exports = self.is_exports_expr(call.args[1].expr.as_ref());
           if is_exports {
-            if let Some(props) = self.as_obj(call.args[0].expr.as_ref()) {
-              self.update_exports_from_object(props);
+            if let Some(desc) = self.as_obj(call.args[0].expr.as_ref()) {
+              self.update_exports_from_object(desc, Provenance::TslibExportStar);
             } else if let Some(reexport) = self.as_reexport(call.args[0].expr.as_ref()) {
-              self.reexports.insert(reexport.path);
+              self.add_reexport(reexport, Provenance::TslibExportStar);
             }
           }
-        } else if is_export_call(&call) && call.args.len() > 0 {
-          if let Some(props) = self.as_obj(call.args[0].expr.as_ref()) {
-            self.update_exports_from_object(props);
+        } else if is_helper_call(call, "__export") && call.args.len() == 1 {
+          if let Some(desc) = self.as_obj(call.args[0].expr.as_ref()) {
+            self.update_exports_from_object(desc, Provenance::ExportHelper);
           } else if let Some(reexport) = self.as_reexport(call.args[0].expr.as_ref()) {
-            self.reexports.insert(reexport.path);
+            self.add_reexport(reexport, Provenance::ExportHelper);
+          }
+        } else if is_helper_call(call, "__export") && call.args.len() >= 2 {
+          // __export(exports, { foo: () => foo })
+          if self.is_exports_expr(call.args[0].expr.as_ref()) {
+            if let Some(desc) = self.as_obj(call.args[1].expr.as_ref()) {
+              self.update_exports_from_getters(desc, Provenance::EsbuildExport);
+            }
+          }
+        } else if self.is_runtime_helper_call(call, "_export") && call.args.len() >= 2 {
+          // _export(exports, { foo: function() { return foo; } })
+          if self.is_exports_expr(call.args[0].expr.as_ref()) {
+            if let Some(desc) = self.as_obj(call.args[1].expr.as_ref()) {
+              self.update_exports_from_getters(desc, Provenance::SwcExport);
+            }
           }
-        } else if let Some(body) = self.is_umd_iife_call(&call) {
+        } else if self.is_runtime_helper_call(call, "_export_star") && call.args.len() >= 2 {
+          // _export_star(require("foo"), exports)
+          if self.is_exports_expr(call.args[1].expr.as_ref()) {
+            if let Some(reexport) = self.as_reexport(call.args[0].expr.as_ref()) {
+              self.add_reexport(reexport, Provenance::SwcExportStar);
+            }
+          }
+        } else if is_helper_call(call, "__reExport") && call.args.len() >= 2 {
+          // __reExport(exports, require("foo"))
+          // __reExport(src_exports, require("foo"), module.exports)
+          let is_exports = self.is_exports_expr(call.args[0].expr.as_ref())
+            || call
+              .args
+              .get(2)
+              .is_some_and(|arg| self.is_exports_expr(arg.expr.as_ref()));
+          if is_exports {
+            if let Some(reexport) = self.as_reexport(call.args[1].expr.as_ref()) {
+              self.add_reexport(reexport, Provenance::EsbuildReexport);
+            }
+          }
+        } else if !call.args.is_empty()
+          && self.is_exports_expr(call.args[0].expr.as_ref())
+          && is_webpack_getters_call(call, self.webpack_runtimes)
+        {
+          // __webpack_require__.r(exports)
+          // __webpack_require__.d(exports, { foo: () => foo })
+          for (name, provenance) in get_webpack_getters(call, self.webpack_runtimes) {
+            self.add_export(name, provenance);
+          }
+        } else if is_helper_call(call, "$parcel$export") && call.args.len() >= 3 {
+          // $parcel$export(module.exports, "foo", () => $abc$export$foo)
+          if self.is_exports_expr(call.args[0].expr.as_ref()) {
+            if let Some(name) = self.as_str(call.args[1].expr.as_ref()) {
+              match get_fn_return_value(call.args[2].expr.as_ref()) {
+                Some(value) => self.add_export_with_value(name, value, Provenance::ParcelExport),
+                None => self.add_export(name, Provenance::ParcelExport),
+              }
+            }
+          }
+        } else if is_helper_call(call, "$parcel$exportWildcard") && call.args.len() >= 2 {
+          // $parcel$exportWildcard(module.exports, $abc$exports)
+          if self.is_exports_expr(call.args[0].expr.as_ref()) {
+            if let Some(reexport) = self.as_reexport(call.args[1].expr.as_ref()) {
+              self.add_reexport(reexport, Provenance::ParcelExportWildcard);
+            }
+          }
+        } else if let Some(reexport) = self.as_export_star_loop(call) {
+          self.add_reexport(reexport, Provenance::BabelExportStar);
+        } else if let Some(const_loop) = self.as_const_loop_call(call) {
+          // ["foo", "bar"].forEach(key => exports[key] = ...)
+          self.add_loop_exports(const_loop);
+        } else if self.is_amd_define_call(call) {
+          self.walk_amd_define(call);
+        } else if is_system_register_call(call) {
+          self.walk_system_register(call);
+        } else if let Some(body) = self.is_umd_iife_call(call) {
           self.walk_body(body, false);
-        } else if let Some(body) = get_iife_body(&call) {
+        } else if let Some(body) = get_iife_body(call) {
           for arg in &call.args {
             if arg.spread.is_none() {
               // (function() { ... })(exports.foo || (exports.foo = {}))
               if let Some(name) = self.get_export_name_from_bin_expr(&arg.expr) {
-                self.named_exports.insert(name);
+                self.add_export(name, Provenance::LogicalAssignment);
               }
             }
           }
           self.walk_body(body, false);
+        } else {
+          // names.forEach(name => exports[name] = ...)
+          for arg in &call.args {
+            if matches!(arg.expr.as_ref(), Expr::Fn(_) | Expr::Arrow(_)) {
+              self.warn_dynamic_exports(arg.expr.as_ref());
+            }
+          }
         }
       }
       // ~function(){ ... }()
       // !(function(e, t) { ... })(this, (function (e) { ... }));
-      Expr::Unary(UnaryExpr { op, arg, .. }) => {
-        if let UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Bang | UnaryOp::Tilde | UnaryOp::Void = op {
-          if let Expr::Call(call) = arg.as_ref() {
-            if let Some(body) = self.is_umd_iife_call(&call) {
-              self.walk_body(body, false);
-            } else if let Some(body) = get_iife_body(&call) {
-              // (function() { ... })(exports.foo || (exports.foo = {}))
-              for arg in &call.args {
-                if arg.spread.is_none() {
-                  if let Some(name) = self.get_export_name_from_bin_expr(&arg.expr) {
-                    self.named_exports.insert(name);
-                  }
+      Expr::Unary(UnaryExpr {
+        op: UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Bang | UnaryOp::Tilde | UnaryOp::Void,
+        arg,
+        ..
+      }) => {
+        if let Expr::Call(call) = arg.as_ref() {
+          if let Some(body) = self.is_umd_iife_call(call) {
+            self.walk_body(body, false);
+          } else if let Some(body) = get_iife_body(call) {
+            // (function() { ... })(exports.foo || (exports.foo = {}))
+            for arg in &call.args {
+              if arg.spread.is_none() {
+                if let Some(name) = self.get_export_name_from_bin_expr(&arg.expr) {
+                  self.add_export(name, Provenance::LogicalAssignment);
                 }
               }
-              self.walk_body(body, false);
             }
+            self.walk_body(body, false);
           }
         }
       }
       // (function(){ ... }())
       Expr::Paren(ParenExpr { expr, .. }) => {
-        self.walk(
-          vec![Stmt::Expr(ExprStmt {
-            span: DUMMY_SP,
-            expr: expr.clone(),
-          })],
-          false,
-        );
+        self.mark_idents_from_expr(expr);
+        self.parse_expr(expr);
       }
       // 0 && (module.exports = { foo })
       // process.env.NODE_ENV === 'production' && (() => { module.exports = { foo } })()
       Expr::Bin(BinExpr { left, op, right, .. }) => {
         if matches!(op, BinaryOp::LogicalAnd) {
           if let Expr::Call(call) = right.as_ref() {
-            if let Some(body) = get_iife_body(&call) {
+            if let Some(body) = get_iife_body(call) {
               if self.is_true(left) {
                 for arg in &call.args {
                   if arg.spread.is_none() {
                     // (function() { ... })(exports.foo || (exports.foo = {}))
                     if let Some(name) = self.get_export_name_from_bin_expr(&arg.expr) {
-                      self.named_exports.insert(name);
+                      self.add_export(name, Provenance::LogicalAssignment);
                     }
                   }
                 }
@@ -1182,8 +1864,12 @@ impl ModuleLexer {
             }
           } else if let Expr::Paren(paren) = right.as_ref() {
             if let Expr::Assign(assign) = paren.expr.as_ref() {
-              if is_lit_number(&left) || self.is_true(&left) {
+              if self.is_true(left) {
+                self.get_exports_from_assign(assign);
+              } else if is_lit_number(left) {
+                self.annotation = true;
                 self.get_exports_from_assign(assign);
+                self.annotation = false;
               }
             }
           }
@@ -1193,24 +1879,27 @@ impl ModuleLexer {
     }
   }
 
-  fn walk(&mut self, stmts: Vec<Stmt>, as_fn: bool) {
-    self.walk_stmts(&stmts);
+  // walk the statements, returns `true` if the statements return unconditionally.
+  fn walk(&mut self, stmts: impl IntoIterator<Item = &'a Stmt, IntoIter: Clone>, as_fn: bool) -> bool {
+    let stmts = stmts.into_iter();
+    self.walk_stmts(stmts.clone());
 
     // check exports (as function)
     if as_fn {
-      for stmt in &stmts {
+      for stmt in stmts {
         if self.fn_returned {
           break;
         }
+        self.enter_stmt(stmt);
         match stmt {
           Stmt::Block(BlockStmt { stmts, .. }) => {
-            self.walk_body(stmts.clone(), true);
+            self.walk_body(Body::Stmts(stmts), true);
           }
           Stmt::If(IfStmt { test, cons, alt, .. }) => {
             if self.is_true(test) {
-              self.walk_body(vec![cons.as_ref().clone()], true);
+              self.walk_body(Body::Stmts(std::slice::from_ref(cons.as_ref())), true);
             } else if let Some(alt) = alt {
-              self.walk_body(vec![alt.as_ref().clone()], true);
+              self.walk_body(Body::Stmts(std::slice::from_ref(alt.as_ref())), true);
             }
           }
           Stmt::Return(ReturnStmt { arg, .. }) => {
@@ -1222,11 +1911,12 @@ impl ModuleLexer {
           _ => {}
         }
       }
-      return;
+      return self.fn_returned;
     }
 
     // check exports
-    for stmt in &stmts {
+    for stmt in stmts {
+      self.enter_stmt(stmt);
       match stmt {
         // var foo = exports.foo || (exports.foo = {})
         // var foo = exports.foo = "bar"
@@ -1235,7 +1925,7 @@ impl ModuleLexer {
             self.mark_exports_alias_from_var_decl(decl);
             if let Some(init_expr) = &decl.init {
               if let Some(name) = self.get_export_name_from_bin_expr(init_expr) {
-                self.named_exports.insert(name);
+                self.add_export(name, Provenance::LogicalAssignment);
               } else {
                 self.parse_expr(init_expr);
               }
@@ -1243,98 +1933,143 @@ impl ModuleLexer {
           }
         }
         Stmt::Expr(ExprStmt { expr, .. }) => self.parse_expr(expr),
-        Stmt::Block(BlockStmt { stmts, .. }) => {
-          self.walk_body(stmts.clone(), false);
+        Stmt::ForIn(for_in) => {
+          if let Some(reexport) = self.as_export_star_for_in(for_in) {
+            self.add_reexport(reexport, Provenance::TslibExportStar);
+          } else if let Some(desc) = self.as_export_copy_for_in(for_in) {
+            self.update_exports_from_object(desc, Provenance::ObjectAssign);
+          } else if let Some(const_loop) = self.as_const_loop_stmt(stmt) {
+            self.add_loop_exports(const_loop);
+          } else {
+            self.warn_dynamic_exports(stmt);
+          }
+        }
+        // for (const key of ["foo", "bar"]) exports[key] = ...
+        Stmt::ForOf(_) => {
+          if let Some(const_loop) = self.as_const_loop_stmt(stmt) {
+            self.add_loop_exports(const_loop);
+          } else {
+            self.warn_dynamic_exports(stmt);
+          }
+        }
+        // for (var i = 0; i < names.length; i++) exports[names[i]] = ...
+        Stmt::For(_) | Stmt::While(_) | Stmt::DoWhile(_) => self.warn_dynamic_exports(stmt),
+        Stmt::Block(BlockStmt { stmts, .. }) if self.walk_body(Body::Stmts(stmts), false) => {
+          return true;
+        }
+        // with (obj) { ... }
+        Stmt::With(WithStmt { body, .. })
+          if self.walk_body(Body::Stmts(std::slice::from_ref(body.as_ref())), false) =>
+        {
+          return true;
         }
         Stmt::If(IfStmt { test, cons, alt, .. }) => {
-          if self.is_true(test) {
-            self.walk_body(vec![cons.as_ref().clone()], false);
+          let returned = if self.is_true(test) {
+            self.walk_body(Body::Stmts(std::slice::from_ref(cons.as_ref())), false)
           } else if let Some(alt) = alt {
-            self.walk_body(vec![alt.as_ref().clone()], false);
+            self.walk_body(Body::Stmts(std::slice::from_ref(alt.as_ref())), false)
+          } else {
+            false
+          };
+          // only an `if` with a known condition returns unconditionally
+          if returned && self.as_known_bool(test).is_some() {
+            return true;
           }
         }
+        // the statements after `return` are unreachable
         Stmt::Return(ReturnStmt { arg, .. }) => {
           if let Some(arg) = arg {
-            match &**arg {
-              Expr::Call(call) => match with_expr_callee(call) {
-                Some(Expr::Fn(FnExpr { function, .. })) => {
-                  if let Function {
-                    body: Some(BlockStmt { stmts, .. }),
-                    ..
-                  } = function.as_ref()
-                  {
-                    let mut check_function = |function: &Box<Function>| {
-                      if let Function {
-                        body: Some(BlockStmt { stmts, .. }),
-                        ..
-                      } = function.as_ref()
-                      {
-                        if let Some(Stmt::If(IfStmt { cons, .. })) = stmts.get(0) {
-                          if let Stmt::Return(ReturnStmt { arg: Some(arg), .. }) = &**cons {
-                            if let Expr::Member(MemberExpr {
-                              prop: MemberProp::Ident(prop),
-                              ..
-                            }) = &**arg
-                            {
-                              if prop.sym.as_ref().eq("exports") {
-                                if call.args.len() != 1 {
-                                  return;
-                                }
-                                if let Some(ExprOrSpread { expr, .. }) = call.args.get(0) {
-                                  if let Expr::Array(ArrayLit { elems, .. }) = &**expr {
-                                    for elem in elems {
-                                      if let Some(ExprOrSpread { expr, .. }) = elem {
-                                        if let Expr::Fn(FnExpr { function, .. }) = &**expr {
-                                          if let Function {
-                                            body: Some(BlockStmt { stmts, .. }),
-                                            params,
+            self.walk_return(arg);
+          }
+          return true;
+        }
+        _ => {}
+      }
+    }
+    false
+  }
+
+  // check the webpack bundles that are returned by the module, returns `true` if the walk should stop.
+  fn walk_return(&mut self, arg: &'a Expr) -> bool {
+    let webpack_getters = get_returned_webpack_getters(arg, self.webpack_runtimes);
+    let found_webpack_exports = !webpack_getters.is_empty();
+    for (name, provenance) in webpack_getters {
+      self.add_export(name, provenance);
+    }
+    if let Expr::Call(call) = arg {
+      match with_expr_callee(call) {
+        Some(Expr::Fn(FnExpr { function, .. })) => {
+          if let Function {
+            body: Some(BlockStmt { stmts, .. }),
+            ..
+          } = function.as_ref()
+          {
+            let mut check_function = |function: &Function| {
+              if let Function {
+                body: Some(BlockStmt { stmts, .. }),
+                ..
+              } = function
+              {
+                if let Some(Stmt::If(IfStmt { cons, .. })) = stmts.first() {
+                  if let Stmt::Return(ReturnStmt { arg: Some(arg), .. }) = &**cons {
+                    if let Expr::Member(MemberExpr {
+                      prop: MemberProp::Ident(prop),
+                      ..
+                    }) = &**arg
+                    {
+                      if prop.sym.as_ref().eq("exports") {
+                        if call.args.len() != 1 {
+                          return;
+                        }
+                        if let Some(ExprOrSpread { expr, .. }) = call.args.first() {
+                          if let Expr::Array(ArrayLit { elems, .. }) = &**expr {
+                            for ExprOrSpread { expr, .. } in elems.iter().flatten() {
+                              if let Expr::Fn(FnExpr { function, .. }) = &**expr {
+                                if let Function {
+                                  body: Some(BlockStmt { stmts, .. }),
+                                  params,
+                                  ..
+                                } = function.as_ref()
+                                {
+                                  if let Some(Param {
+                                    pat:
+                                      Pat::Ident(BindingIdent {
+                                        id:
+                                          Ident {
+                                            sym: webpack_exports_sym,
                                             ..
-                                          } = function.as_ref()
-                                          {
-                                            if let Some(Param {
-                                              pat:
-                                                Pat::Ident(BindingIdent {
-                                                  id:
-                                                    Ident {
-                                                      sym: webpack_exports_sym,
-                                                      ..
-                                                    },
-                                                  ..
-                                                }),
+                                          },
+                                        ..
+                                      }),
+                                    ..
+                                  }) = params.get(1)
+                                  {
+                                    if let Some(Param {
+                                      pat:
+                                        Pat::Ident(BindingIdent {
+                                          id:
+                                            Ident {
+                                              sym: webpack_require_sym,
                                               ..
-                                            }) = params.get(1)
-                                            {
-                                              if let Some(Param {
-                                                pat:
-                                                  Pat::Ident(BindingIdent {
-                                                    id:
-                                                      Ident {
-                                                        sym: webpack_require_sym,
-                                                        ..
-                                                      },
-                                                    ..
-                                                  }),
-                                                ..
-                                              }) = params.get(2)
-                                              {
-                                                for stmt in stmts {
-                                                  if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
-                                                    self.get_webpack4_exports(
-                                                      expr,
-                                                      webpack_exports_sym.as_ref(),
-                                                      Some(webpack_require_sym.as_ref()),
-                                                    )
-                                                  }
-                                                }
-                                              } else {
-                                                for stmt in stmts {
-                                                  if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
-                                                    self.get_webpack4_exports(expr, webpack_exports_sym.as_ref(), None)
-                                                  }
-                                                }
-                                              }
-                                            }
-                                          }
+                                            },
+                                          ..
+                                        }),
+                                      ..
+                                    }) = params.get(2)
+                                    {
+                                      for stmt in stmts {
+                                        if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
+                                          self.get_webpack4_exports(
+                                            expr,
+                                            webpack_exports_sym.as_ref(),
+                                            Some(webpack_require_sym.as_ref()),
+                                          )
+                                        }
+                                      }
+                                    } else {
+                                      for stmt in stmts {
+                                        if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
+                                          self.get_webpack4_exports(expr, webpack_exports_sym.as_ref(), None)
                                         }
                                       }
                                     }
@@ -1345,228 +2080,504 @@ impl ModuleLexer {
                           }
                         }
                       }
-                    };
-                    if let Some(Stmt::Decl(Decl::Fn(FnDecl { function, .. }))) = stmts.get(0) {
-                      check_function(function);
-                    } else if let Some(Stmt::Decl(Decl::Fn(FnDecl { function, .. }))) = stmts.get(1) {
-                      check_function(function);
                     }
                   }
                 }
-                Some(Expr::Paren(ParenExpr { expr, .. })) => {
-                  if let Expr::Arrow(ArrowExpr { body, .. }) = &**expr {
-                    if let BlockStmtOrExpr::BlockStmt(BlockStmt { stmts, .. }) = &**body {
-                      let first_stmt_index = match stmts.get(0) {
-                        Some(Stmt::Expr(ExprStmt { expr, .. })) => match &**expr {
-                          Expr::Lit(Lit::Str(Str { value, .. })) => {
-                            if value.to_string_lossy().eq("use strict") {
-                              1
-                            } else {
-                              0
-                            }
-                          }
-                          _ => 0,
-                        },
-                        _ => 0,
-                      };
-
-                      if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.get(first_stmt_index) {
-                        let VarDecl { decls, .. } = &**var_decl;
-                        match decls.get(0) {
-                          Some(VarDeclarator {
-                            name:
-                              Pat::Ident(BindingIdent {
-                                id:
-                                  Ident {
-                                    sym: webpack_require_sym,
-                                    ..
-                                  },
-                                ..
-                              }),
-                            init,
-                            ..
-                          }) => {
-                            if let Some(init) = init {
-                              if let Expr::Object(ObjectLit { props, .. }) = &**init {
-                                let webpack_require_props = self.get_webpack_require_props_from_props(props);
-
-                                if webpack_require_props == 2 {
-                                  self.get_webpack_exports(stmts, &webpack_require_sym, &(first_stmt_index + 1));
-                                }
-                              }
-                            }
-                          }
-                          _ => {}
-                        }
-                      }
-
-                      if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.get(first_stmt_index) {
-                        let VarDecl { decls, .. } = &**var_decl;
-                        match decls.get(0) {
-                          Some(VarDeclarator {
-                            name:
-                              Pat::Ident(BindingIdent {
-                                id:
-                                  Ident {
-                                    sym: webpack_require_sym,
-                                    ..
-                                  },
-                                ..
-                              }),
-                            init,
-                            ..
-                          }) => {
-                            if let Some(init) = init {
-                              if let Expr::Object(ObjectLit { props, .. }) = &**init {
-                                let webpack_require_props = self.get_webpack_require_props_from_props(props);
-
-                                if webpack_require_props == 2 {
-                                  self.get_webpack_exports(stmts, &webpack_require_sym, &(first_stmt_index + 1));
-                                }
-                              }
-                            }
-                          }
-                          _ => {}
-                        }
-                      }
-
-                      if let Some(Stmt::Decl(Decl::Fn(FnDecl {
-                        ident:
-                          Ident {
-                            sym: webpack_require_sym,
+              }
+            };
+            for stmt in stmts {
+              if let Stmt::Decl(Decl::Fn(FnDecl { function, .. })) = stmt {
+                check_function(function);
+              }
+            }
+          }
+        }
+        Some(Expr::Paren(ParenExpr { expr, .. })) => {
+          if let Expr::Arrow(ArrowExpr { body, .. }) = &**expr {
+            if let BlockStmtOrExpr::BlockStmt(BlockStmt { stmts, .. }) = &**body {
+              if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) = stmts.last() {
+                if let Expr::Seq(SeqExpr { exprs, .. }) = &**arg {
+                  if let Some(module_exports_expr) = exprs.last() {
+                    if let Some(module_iife_expr) = exprs.first() {
+                      if let Expr::Call(module_iife_call_expr) = &**module_iife_expr {
+                        if let Some(Body::Stmts(stmts)) = get_iife_body(module_iife_call_expr) {
+                          if let Expr::Ident(Ident {
+                            sym: module_exports_sym,
                             ..
-                          },
-                        ..
-                      }))) = stmts.get(first_stmt_index + 1)
-                      {
-                        let webpack_require_props =
-                          self.get_webpack_require_props_from_stmts(stmts, webpack_require_sym);
-                        if webpack_require_props == 2 {
-                          if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) = stmts.get(stmts.len() - 1) {
-                            if let Expr::Seq(SeqExpr { exprs, .. }) = &**arg {
-                              if let Some(expr) = exprs.get(0) {
-                                if let Expr::Call(call) = &**expr {
-                                  if let Some(stmts) = get_iife_body(call) {
-                                    self.get_webpack_exports(&stmts, &webpack_require_sym, &0);
+                          }) = &**module_exports_expr
+                          {
+                            if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.first() {
+                              let VarDecl { decls, .. } = &**var_decl;
+                              if let Some(VarDeclarator {
+                                name,
+                                init: Some(init_expr),
+                                ..
+                              }) = decls.first()
+                              {
+                                if let Expr::Ident(Ident { sym, .. }) = &**init_expr {
+                                  if module_exports_sym.as_ref().eq(sym.as_ref()) {
+                                    if let Pat::Ident(BindingIdent {
+                                      id: Ident { sym, .. }, ..
+                                    }) = name
+                                    {
+                                      self.exports_alias.insert(sym.as_ref().to_owned(), ());
+                                      self.walk_body(Body::Stmts(stmts), false);
+                                      return true;
+                                    }
                                   }
                                 }
                               }
                             }
-                          }
-                        }
-                      }
-
-                      if let Some(Stmt::Return(ReturnStmt { arg, .. })) = stmts.get(stmts.len() - 1) {
-                        if let Some(arg) = arg {
-                          match &**arg {
-                            Expr::Seq(SeqExpr { exprs, .. }) => {
-                              if let Some(module_exports_expr) = exprs.get(exprs.len() - 1) {
-                                if let Some(module_iife_expr) = exprs.get(0) {
-                                  if let Expr::Call(module_iife_call_expr) = &**module_iife_expr {
-                                    if let Some(stmts) = get_iife_body(module_iife_call_expr) {
-                                      if let Expr::Ident(Ident {
-                                        sym: module_exports_sym,
-                                        ..
-                                      }) = &**module_exports_expr
-                                      {
-                                        if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.get(0) {
-                                          let VarDecl { decls, .. } = &**var_decl;
-                                          if let Some(VarDeclarator { name, init, .. }) = decls.get(0) {
-                                            if let Some(init_expr) = init {
-                                              if let Expr::Ident(Ident { sym, .. }) = &**init_expr {
-                                                if module_exports_sym.as_ref().eq(sym.as_ref()) {
-                                                  if let Pat::Ident(BindingIdent {
-                                                    id: Ident { sym, .. }, ..
-                                                  }) = name
-                                                  {
-                                                    self.exports_alias.insert(sym.as_ref().to_owned());
-                                                    self.walk_body(stmts, false);
-                                                    return;
-                                                  }
-                                                }
-                                              }
-                                            }
-                                          }
-                                        }
 
-                                        if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.get(1) {
-                                          let VarDecl { decls, .. } = &**var_decl;
-                                          if let Some(VarDeclarator { name, init, .. }) = decls.get(0) {
-                                            if let Some(init_expr) = init {
-                                              if let Expr::Ident(Ident { sym, .. }) = &**init_expr {
-                                                if module_exports_sym.as_ref().eq(sym.as_ref()) {
-                                                  if let Pat::Ident(BindingIdent {
-                                                    id: Ident { sym, .. }, ..
-                                                  }) = name
-                                                  {
-                                                    self.exports_alias.insert(sym.as_ref().to_owned());
-                                                    self.walk_body(stmts, false);
-                                                    return;
-                                                  }
-                                                }
-                                              }
-                                            }
-                                          }
-                                        }
-                                      }
+                            if let Some(Stmt::Decl(Decl::Var(var_decl))) = stmts.get(1) {
+                              let VarDecl { decls, .. } = &**var_decl;
+                              if let Some(VarDeclarator {
+                                name,
+                                init: Some(init_expr),
+                                ..
+                              }) = decls.first()
+                              {
+                                if let Expr::Ident(Ident { sym, .. }) = &**init_expr {
+                                  if module_exports_sym.as_ref().eq(sym.as_ref()) {
+                                    if let Pat::Ident(BindingIdent {
+                                      id: Ident { sym, .. }, ..
+                                    }) = name
+                                    {
+                                      self.exports_alias.insert(sym.as_ref().to_owned(), ());
+                                      self.walk_body(Body::Stmts(stmts), false);
+                                      return true;
                                     }
                                   }
                                 }
                               }
                             }
-                            _ => {}
                           }
                         }
                       }
                     }
                   }
                 }
-                _ => {}
-              },
-              _ => {}
+              }
             }
           }
         }
         _ => {}
       }
     }
+    found_webpack_exports
   }
 
-  fn walk_body(&mut self, body: Vec<Stmt>, as_fn: bool) {
-    let mut lexer = ModuleLexer {
-      node_env: self.node_env.to_owned(),
-      call_mode: false,
-      fn_returned: false,
-      idents: self.idents.clone(),
-      exports_alias: self.exports_alias.clone(),
-      named_exports: self.named_exports.clone(),
-      reexports: self.reexports.clone(),
-    };
-    lexer.walk(body, as_fn);
-    self.fn_returned = lexer.fn_returned;
-    self.named_exports = lexer.named_exports;
-    self.reexports = lexer.reexports;
+  // define(["exports", "dep"], function (exports, dep) { ... })
+  // define(function (require, exports, module) { ... })
+  // define({ foo: "bar" })
+  fn is_amd_define_call(&self, call: &CallExpr) -> bool {
+    matches!(with_expr_callee(call), Some(Expr::Ident(id)) if id.sym.as_ref().eq("define"))
+      && !self.idents.contains_key("define")
+      && !call.args.is_empty()
+      && call.args.iter().all(|arg| arg.spread.is_none())
   }
-}
 
-impl Fold for ModuleLexer {
-  noop_fold_type!();
+  fn walk_amd_define(&mut self, call: &'a CallExpr) {
+    // define("name", [...], factory)
+    let args = match call.args[0].expr.as_ref() {
+      Expr::Lit(Lit::Str(_)) => &call.args[1..],
+      _ => &call.args[..],
+    };
+    let (deps, factory) = match args {
+      [factory] => (None, factory.expr.as_ref()),
+      [deps, factory] => match deps.expr.as_ref() {
+        Expr::Array(ArrayLit { elems, .. }) => {
+          let deps = elems
+            .iter()
+            .map(|elem| elem.as_ref().and_then(|elem| self.as_str(&elem.expr)))
+            .collect::<Vec<_>>();
+          (Some(deps), factory.expr.as_ref())
+        }
+        _ => return,
+      },
+      _ => return,
+    };
+    let (params, body) = match factory {
+      Expr::Fn(FnExpr { function, .. }) => match &function.body {
+        Some(BlockStmt { stmts, .. }) => (
+          function.params.iter().map(|param| &param.pat).collect::<Vec<_>>(),
+          Body::Stmts(stmts),
+        ),
+        None => return,
+      },
+      Expr::Arrow(arrow) => (arrow.params.iter().collect(), get_arrow_body(arrow)),
+      // define({ foo: "bar" })
+      Expr::Object(_) => {
+        self.replace_exports_from_expr(factory);
+        return;
+      }
+      _ => return,
+    };
+    // the factory gets `require`, `exports` and `module` if the dependencies are omitted
+    let deps = deps.unwrap_or_else(|| {
+      ["require", "exports", "module"]
+        .iter()
+        .map(|dep| Some(dep.to_string()))
+        .collect()
+    });
+
+    self.idents.enter();
+    self.exports_alias.enter();
+    for (param, dep) in params.into_iter().zip(deps) {
+      if let (Pat::Ident(BindingIdent { id, .. }), Some(dep)) = (param, dep) {
+        let name = id.sym.as_ref().to_owned();
+        match dep.as_str() {
+          "exports" => self.exports_alias.insert(name, ()),
+          "require" | "module" => {}
+          _ => self.idents.insert(name, IdentKind::Reexport(Reexport::new(dep))),
+        }
+      }
+    }
+    match body {
+      Body::Stmts(stmts) => {
+        self.walk(stmts, false);
+        // the returned value of the factory is the exports of the module
+        if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) =
+          stmts.iter().find(|stmt| matches!(stmt, Stmt::Return(_)))
+        {
+          self.replace_exports_from_expr(arg);
+        }
+      }
+      Body::Expr(expr) => self.replace_exports_from_expr(expr),
+    }
+    self.idents.leave();
+    self.exports_alias.leave();
+  }
 
-  fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
-    let stmts = items
+  // System.register(["dep"], function (_export, _context) {
+  //   return {
+  //     setters: [function (_dep) { _export("bar", _dep.bar) }],
+  //     execute: function () { _export("foo", 1) }
+  //   };
+  // })
+  fn walk_system_register(&mut self, call: &'a CallExpr) {
+    // System.register("name", [...], declare)
+    let args = match call.args[0].expr.as_ref() {
+      Expr::Lit(Lit::Str(_)) => &call.args[1..],
+      _ => &call.args[..],
+    };
+    let [deps, declare] = args else {
+      return;
+    };
+    let Expr::Array(ArrayLit { elems, .. }) = deps.expr.as_ref() else {
+      return;
+    };
+    let deps = elems
       .iter()
-      .filter(|&item| match item {
-        ModuleItem::Stmt(_) => true,
-        _ => false,
-      })
-      .map(|item| match item {
-        ModuleItem::Stmt(stmt) => stmt.clone(),
-        _ => Stmt::Empty(EmptyStmt { span: DUMMY_SP }),
-      })
-      .filter(|item| !item.is_empty())
-      .collect::<Vec<Stmt>>();
+      .map(|elem| elem.as_ref().and_then(|elem| self.as_str(&elem.expr)))
+      .collect::<Vec<_>>();
+    let Some((params, Body::Stmts(stmts))) = get_fn_params_and_body(declare.expr.as_ref()) else {
+      return;
+    };
+    let Some(Pat::Ident(BindingIdent { id: export_id, .. })) = params.first() else {
+      return;
+    };
+    let export_name = export_id.sym.as_ref();
+
+    self.idents.enter();
+    self.walk_stmts(stmts);
+    // the hoisted function declarations are exported before the `return`
+    self.get_system_exports_from_stmts(stmts, export_name);
+    if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) =
+      stmts.iter().find(|stmt| matches!(stmt, Stmt::Return(_)))
+    {
+      if let Expr::Object(ObjectLit { props, .. }) = arg.as_ref() {
+        for prop in props {
+          let PropOrSpread::Prop(prop) = prop else {
+            continue;
+          };
+          let (key, value) = match prop.as_ref() {
+            Prop::KeyValue(KeyValueProp { key, value }) => (key, Some(value.as_ref())),
+            Prop::Method(MethodProp { key, .. }) => (key, None),
+            _ => continue,
+          };
+          match (stringify_prop_name(key).as_deref(), value) {
+            // setters: [function (_dep) { ... }]
+            (Some("setters"), Some(Expr::Array(ArrayLit { elems, .. }))) => {
+              for (setter, dep) in elems.iter().zip(&deps) {
+                let Some((params, Body::Stmts(stmts))) = setter.as_ref().and_then(|s| get_fn_params_and_body(&s.expr))
+                else {
+                  continue;
+                };
+                self.idents.enter();
+                if let (Some(Pat::Ident(BindingIdent { id, .. })), Some(dep)) = (params.first(), dep) {
+                  let reexport = Reexport::new(dep.clone());
+                  self
+                    .idents
+                    .insert(id.sym.as_ref().to_owned(), IdentKind::Reexport(reexport));
+                }
+                self.walk_stmts(stmts);
+                self.get_system_exports_from_stmts(stmts, export_name);
+                self.idents.leave();
+              }
+            }
+            // execute: function () { ... }
+            (Some("execute"), _) => {
+              let body = match (value, prop.as_ref()) {
+                (Some(value), _) => get_fn_params_and_body(value).map(|(_, body)| body),
+                (None, Prop::Method(MethodProp { function, .. })) => {
+                  function.body.as_ref().map(|body| Body::Stmts(&body.stmts))
+                }
+                _ => None,
+              };
+              if let Some(Body::Stmts(stmts)) = body {
+                self.idents.enter();
+                self.walk_stmts(stmts);
+                self.get_system_exports_from_stmts(stmts, export_name);
+                self.idents.leave();
+              }
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+    self.idents.leave();
+  }
+
+  fn get_system_exports_from_stmts(&mut self, stmts: &'a [Stmt], export_name: &str) {
+    for stmt in stmts {
+      match stmt {
+        Stmt::Expr(ExprStmt { expr, .. }) => self.get_system_exports_from_expr(expr, export_name),
+        Stmt::Decl(Decl::Var(var)) => {
+          for decl in &var.decls {
+            if let Some(init) = &decl.init {
+              self.get_system_exports_from_expr(init, export_name);
+            }
+          }
+        }
+        Stmt::Block(BlockStmt { stmts, .. }) => self.get_system_exports_from_stmts(stmts, export_name),
+        Stmt::If(IfStmt { cons, alt, .. }) => {
+          self.get_system_exports_from_stmts(std::slice::from_ref(cons.as_ref()), export_name);
+          if let Some(alt) = alt {
+            self.get_system_exports_from_stmts(std::slice::from_ref(alt.as_ref()), export_name);
+          }
+        }
+        // for (var _key in _dep) { if (_key !== "default") _exportObj[_key] = _dep[_key]; }
+        Stmt::ForIn(for_in) => {
+          if let (Some(key), Some(reexport)) = (get_for_in_key(for_in), self.as_reexport(&for_in.right)) {
+            if let Some(target) = get_key_assign_target(&for_in.body, key.sym.as_ref()) {
+              self.idents.insert(target, IdentKind::Reexport(reexport));
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  // _export("foo", foo)
+  // _export({ foo: foo, bar: _dep.bar })
+  // _export(_exportObj)
+  fn get_system_exports_from_expr(&mut self, expr: &'a Expr, export_name: &str) {
+    match expr {
+      Expr::Paren(ParenExpr { expr, .. }) => self.get_system_exports_from_expr(expr, export_name),
+      Expr::Seq(SeqExpr { exprs, .. }) => {
+        for expr in exprs {
+          self.get_system_exports_from_expr(expr, export_name);
+        }
+      }
+      Expr::Assign(AssignExpr { right, .. }) => self.get_system_exports_from_expr(right, export_name),
+      Expr::Call(call) if matches!(with_expr_callee(call), Some(Expr::Ident(id)) if id.sym.as_ref().eq(export_name)) => {
+        match call.args.as_slice() {
+          [name, value] => {
+            if let Some(name) = self.as_str(&name.expr) {
+              self.add_export_with_value(name, &value.expr, Provenance::SystemExport);
+            }
+          }
+          [value] => {
+            if let Some(reexport) = self.as_reexport(&value.expr) {
+              self.add_reexport(reexport, Provenance::SystemExport);
+            } else if let Some(desc) = self.as_obj(&value.expr) {
+              self.update_exports_from_object(desc, Provenance::SystemExport);
+            }
+          }
+          _ => {}
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // walk the body in a new scope, the idents and exports aliases that are marked in the body are dropped after
+  fn walk_body(&mut self, body: Body<'a>, as_fn: bool) -> bool {
+    let call_mode = std::mem::replace(&mut self.call_mode, false);
+    let span = self.span;
+    let annotation = self.annotation;
+    self.fn_returned = false;
+    self.idents.enter();
+    self.exports_alias.enter();
+    let returned = match body {
+      Body::Stmts(stmts) => self.walk(stmts, as_fn),
+      // () => expr
+      Body::Expr(expr) => {
+        if as_fn {
+          self.fn_returned = true;
+          self.replace_exports_from_expr(expr);
+        } else {
+          self.walk_return(expr);
+        }
+        // the expression body returns from the arrow function, not from the enclosing body
+        as_fn
+      }
+    };
+    self.idents.leave();
+    self.exports_alias.leave();
+    self.call_mode = call_mode;
+    self.span = span;
+    self.annotation = annotation;
+    returned
+  }
+
+  /// walk the statements of the module, the module declarations are ignored.
+  pub fn walk_module(&mut self, module: &'a Module) {
+    let stmts = module.body.iter().filter_map(|item| match item {
+      ModuleItem::Stmt(stmt) if !stmt.is_empty() => Some(stmt),
+      _ => None,
+    });
     self.walk(stmts, false);
-    items
+  }
+
+  // warn about the dynamic exports in a loop or a callback that can't be unrolled,
+  // e.g. `for (var i = 0; i < names.length; i++) exports[names[i]] = 1`
+  fn warn_dynamic_exports<N: for<'l> VisitWith<DynamicExportsVisitor<'l, 'a>>>(&mut self, node: &N) {
+    let mut visitor = DynamicExportsVisitor {
+      lexer: self,
+      warnings: vec![],
+    };
+    node.visit_with(&mut visitor);
+    for (kind, span) in visitor.warnings {
+      self.add_warning(kind, span);
+    }
+  }
+}
+
+/// get the names that are mutated as arrays in the module:
+/// names.push(name)
+/// names[i] = name
+pub(crate) fn get_mutated_arrays(module: &Module) -> HashSet<String> {
+  let mut visitor = MutatedArrayVisitor { names: HashSet::new() };
+  module.visit_with(&mut visitor);
+  visitor.names
+}
+
+struct MutatedArrayVisitor {
+  names: HashSet<String>,
+}
+
+impl Visit for MutatedArrayVisitor {
+  fn visit_call_expr(&mut self, call: &CallExpr) {
+    if let Some(Expr::Member(MemberExpr { obj, prop, .. })) = with_expr_callee(call) {
+      if let (Expr::Ident(id), Some(method)) = (obj.as_ref(), get_prop_name(prop)) {
+        if matches!(
+          method.as_str(),
+          "push" | "pop" | "shift" | "unshift" | "splice" | "fill" | "copyWithin"
+        ) {
+          self.names.insert(id.sym.as_ref().to_owned());
+        }
+      }
+    }
+    call.visit_children_with(self);
+  }
+
+  fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+    if let AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr { obj, prop, .. })) = &assign.left {
+      let is_elem_or_length = match prop {
+        MemberProp::Computed(_) => true,
+        _ => get_prop_name(prop).as_deref() == Some("length"),
+      };
+      if let (Expr::Ident(id), true) = (obj.as_ref(), is_elem_or_length) {
+        self.names.insert(id.sym.as_ref().to_owned());
+      }
+    }
+    assign.visit_children_with(self);
+  }
+}
+
+/// get the names that are bound to the webpack runtime in the module:
+/// var e = { d: (t, o) => { ... }, r: (e) => { ... } }
+/// __webpack_require__.d = (exports, definition) => { ... }
+pub(crate) fn get_webpack_runtimes(module: &Module) -> HashSet<String> {
+  let mut visitor = WebpackRuntimeVisitor { names: HashSet::new() };
+  module.visit_with(&mut visitor);
+  visitor.names
+}
+
+struct WebpackRuntimeVisitor {
+  names: HashSet<String>,
+}
+
+impl Visit for WebpackRuntimeVisitor {
+  fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+    if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) = (&decl.name, &decl.init) {
+      if let Expr::Object(ObjectLit { props, .. }) = init.as_ref() {
+        let is_runtime = props.iter().any(|prop| match prop {
+          PropOrSpread::Prop(prop) => match prop.as_ref() {
+            Prop::KeyValue(KeyValueProp { key, value }) => {
+              matches!(stringify_prop_name(key).as_deref(), Some("d" | "r"))
+                && matches!(value.as_ref(), Expr::Fn(_) | Expr::Arrow(_))
+            }
+            _ => false,
+          },
+          PropOrSpread::Spread(_) => false,
+        });
+        if is_runtime {
+          self.names.insert(id.sym.as_ref().to_owned());
+        }
+      }
+    }
+    decl.visit_children_with(self);
+  }
+
+  fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+    if let AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr { obj, prop, .. })) = &assign.left {
+      if let Expr::Ident(id) = obj.as_ref() {
+        if matches!(get_prop_name(prop).as_deref(), Some("d" | "r"))
+          && matches!(assign.right.as_ref(), Expr::Fn(_) | Expr::Arrow(_))
+        {
+          self.names.insert(id.sym.as_ref().to_owned());
+        }
+      }
+    }
+    assign.visit_children_with(self);
+  }
+}
+
+// collects the dynamic export assignments and property definitions in a node
+struct DynamicExportsVisitor<'l, 'a> {
+  lexer: &'l ModuleLexer<'a>,
+  warnings: Vec<(WarningKind, Span)>,
+}
+
+impl Visit for DynamicExportsVisitor<'_, '_> {
+  fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+    if let AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+      obj,
+      prop: MemberProp::Computed(ComputedPropName { expr, .. }),
+      ..
+    })) = &assign.left
+    {
+      if self.lexer.as_str(expr).is_none() && self.lexer.is_exports_expr(obj) {
+        self.warnings.push((WarningKind::DynamicAssignment, assign.span));
+      }
+    }
+    assign.visit_children_with(self);
+  }
+
+  fn visit_call_expr(&mut self, call: &CallExpr) {
+    if is_define_property_call(call)
+      && call.args.len() >= 2
+      && self.lexer.is_exports_expr(&call.args[0].expr)
+      && self.lexer.as_str(&call.args[1].expr).is_none()
+    {
+      self.warnings.push((WarningKind::DynamicDefineProperty, call.span));
+    }
+    call.visit_children_with(self);
   }
 }
 
@@ -1581,24 +2592,58 @@ fn is_module_ident(expr: &Expr) -> bool {
 }
 
 fn is_member(expr: &Expr, obj_name: &str, prop_name: &str) -> bool {
-  if let Some(member_prop_name) = get_member_prop_name(expr, obj_name) {
-    return member_prop_name.eq(prop_name);
+  match expr {
+    Expr::Member(member) => is_member_expr(member, obj_name, prop_name),
+    _ => false,
   }
-  false
 }
 
-fn is_member_member(expr: &Expr, obj_name: &str, middle_obj_name: &str, prop_name: &str) -> bool {
-  if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
-    if is_member(obj, obj_name, middle_obj_name) {
-      if let Some(name) = get_prop_name(prop) {
-        return name.eq(prop_name);
+fn is_member_expr(member: &MemberExpr, obj_name: &str, prop_name: &str) -> bool {
+  match member.obj.as_ref() {
+    Expr::Ident(obj) => {
+      obj.sym.as_ref().eq(obj_name) && get_prop_name(&member.prop).is_some_and(|name| name.eq(prop_name))
+    }
+    _ => false,
+  }
+}
+
+// match:
+// process.env.NODE_ENV -> "process.env.NODE_ENV"
+// process.env["NODE_ENV"] -> "process.env.NODE_ENV"
+// typeof window -> "typeof window"
+// __DEV__ -> "__DEV__"
+fn get_define_key(expr: &Expr) -> Option<String> {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => get_define_key(expr),
+    Expr::Ident(id) => Some(id.sym.as_ref().to_owned()),
+    Expr::Member(MemberExpr { obj, prop, .. }) => {
+      let obj = get_define_key(obj)?;
+      if obj.starts_with("typeof ") {
+        return None;
       }
+      let prop = match prop {
+        MemberProp::Ident(prop) => prop.sym.as_ref().to_owned(),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match expr.as_ref() {
+          Expr::Lit(Lit::Str(Str { value, .. })) => value.to_string_lossy().into_owned(),
+          _ => return None,
+        },
+        _ => return None,
+      };
+      Some(format!("{}.{}", obj, prop))
     }
+    Expr::Unary(UnaryExpr {
+      op: UnaryOp::TypeOf,
+      arg,
+      ..
+    }) => match arg.as_ref() {
+      Expr::Ident(_) | Expr::Member(_) => Some(format!("typeof {}", get_define_key(arg)?)),
+      _ => None,
+    },
+    _ => None,
   }
-  false
 }
 
-fn with_expr_callee(call: &CallExpr) -> Option<&Expr> {
+pub(crate) fn with_expr_callee(call: &CallExpr) -> Option<&Expr> {
   match &call.callee {
     Callee::Expr(callee) => Some(callee.as_ref()),
     _ => None,
@@ -1606,9 +2651,9 @@ fn with_expr_callee(call: &CallExpr) -> Option<&Expr> {
 }
 
 // require('lib')
-fn with_require_call(call: &CallExpr) -> Option<String> {
+pub(crate) fn with_require_call(call: &CallExpr) -> Option<String> {
   if let Some(Expr::Ident(id)) = with_expr_callee(call) {
-    if id.sym.as_ref().eq("require") && call.args.len() > 0 {
+    if id.sym.as_ref().eq("require") && !call.args.is_empty() {
       return match call.args[0].expr.as_ref() {
         Expr::Lit(Lit::Str(Str { value, .. })) => Some(value.to_string_lossy().into_owned()),
         _ => None,
@@ -1618,6 +2663,13 @@ fn with_require_call(call: &CallExpr) -> Option<String> {
   None
 }
 
+// require("process") or require("node:process")
+fn is_builtin_reexport(reexport: &Reexport, name: &str) -> bool {
+  !reexport.called
+    && reexport.member.is_none()
+    && reexport.specifier.strip_prefix("node:").unwrap_or(&reexport.specifier) == name
+}
+
 // match:
 // Object.defineProperty()
 // Object.assgin()
@@ -1628,11 +2680,19 @@ fn is_object_static_mothod_call(call: &CallExpr, method: &str) -> bool {
   false
 }
 
-fn is_umd_params(params: &Vec<Pat>) -> bool {
+// Object.defineProperty() or Reflect.defineProperty()
+fn is_define_property_call(call: &CallExpr) -> bool {
+  match with_expr_callee(call) {
+    Some(callee) => is_member(callee, "Object", "defineProperty") || is_member(callee, "Reflect", "defineProperty"),
+    None => false,
+  }
+}
+
+fn is_umd_params<'p>(mut params: impl ExactSizeIterator<Item = &'p Pat>) -> bool {
   if params.len() == 2 {
-    if let Pat::Ident(bid) = &params.get(0).unwrap() {
+    if let Some(Pat::Ident(bid)) = params.next() {
       if bid.id.sym.eq("global") {
-        if let Pat::Ident(bid) = &params.get(1).unwrap() {
+        if let Some(Pat::Ident(bid)) = params.next() {
           if bid.id.sym.eq("factory") {
             return true;
           }
@@ -1644,7 +2704,7 @@ fn is_umd_params(params: &Vec<Pat>) -> bool {
 }
 
 fn is_string_literal(expr: &Expr, value: &str) -> bool {
-  match &*expr {
+  match expr {
     Expr::Lit(Lit::Str(Str {
       value: literal_value, ..
     })) => literal_value.eq(value),
@@ -1653,19 +2713,17 @@ fn is_string_literal(expr: &Expr, value: &str) -> bool {
 }
 
 fn is_lit_number(expr: &Expr) -> bool {
-  match expr {
-    Expr::Lit(Lit::Num(_)) => true,
-    _ => false,
-  }
+  matches!(expr, Expr::Lit(Lit::Num(_)))
 }
 
 fn is_typeof(expr: &Expr, identifier: &str) -> bool {
-  match &*expr {
-    Expr::Unary(UnaryExpr { arg, op, .. }) => match op {
-      UnaryOp::TypeOf => match &**arg {
-        Expr::Ident(ident) => ident.sym.eq(identifier),
-        _ => false,
-      },
+  match expr {
+    Expr::Unary(UnaryExpr {
+      arg,
+      op: UnaryOp::TypeOf,
+      ..
+    }) => match &**arg {
+      Expr::Ident(ident) => ident.sym.eq(identifier),
       _ => false,
     },
     _ => false,
@@ -1673,28 +2731,30 @@ fn is_typeof(expr: &Expr, identifier: &str) -> bool {
 }
 
 fn is_umd_exports_check(expr: &Expr) -> bool {
-  match &*expr {
-    Expr::Bin(BinExpr { left, right, op, .. }) => match op {
-      BinaryOp::EqEq | BinaryOp::EqEqEq => {
-        (is_typeof(&left, "exports") && is_string_literal(&right, "object"))
-          || (is_typeof(&right, "exports") && is_string_literal(&left, "object"))
-      }
-      _ => false,
-    },
+  match expr {
+    Expr::Bin(BinExpr {
+      left,
+      right,
+      op: BinaryOp::EqEq | BinaryOp::EqEqEq,
+      ..
+    }) => {
+      (is_typeof(left, "exports") && is_string_literal(right, "object"))
+        || (is_typeof(right, "exports") && is_string_literal(left, "object"))
+    }
     _ => false,
   }
 }
 
 fn is_umd_module_check(expr: &Expr) -> bool {
-  match &*expr {
+  match expr {
     Expr::Bin(BinExpr { left, right, op, .. }) => match op {
       BinaryOp::EqEq | BinaryOp::EqEqEq => {
-        (is_typeof(&left, "module") && is_string_literal(&right, "object"))
-          || (is_typeof(&right, "module") && is_string_literal(&left, "object"))
+        (is_typeof(left, "module") && is_string_literal(right, "object"))
+          || (is_typeof(right, "module") && is_string_literal(left, "object"))
       }
       BinaryOp::NotEq | BinaryOp::NotEqEq => {
-        (is_typeof(&left, "module") && is_string_literal(&right, "undefined"))
-          || (is_typeof(&right, "module") && is_string_literal(&left, "undefined"))
+        (is_typeof(left, "module") && is_string_literal(right, "undefined"))
+          || (is_typeof(right, "module") && is_string_literal(left, "undefined"))
       }
       _ => false,
     },
@@ -1703,14 +2763,16 @@ fn is_umd_module_check(expr: &Expr) -> bool {
 }
 
 fn is_umd_define_check(expr: &Expr) -> bool {
-  match &*expr {
-    Expr::Bin(BinExpr { left, right, op, .. }) => match op {
-      BinaryOp::EqEq | BinaryOp::EqEqEq => {
-        (is_typeof(&left, "define") && is_string_literal(&right, "function"))
-          || (is_typeof(&right, "define") && is_string_literal(&left, "function"))
-      }
-      _ => false,
-    },
+  match expr {
+    Expr::Bin(BinExpr {
+      left,
+      right,
+      op: BinaryOp::EqEq | BinaryOp::EqEqEq,
+      ..
+    }) => {
+      (is_typeof(left, "define") && is_string_literal(right, "function"))
+        || (is_typeof(right, "define") && is_string_literal(left, "function"))
+    }
     _ => false,
   }
 }
@@ -1722,8 +2784,8 @@ fn is_umd_define_check(expr: &Expr) -> bool {
 // var r = 'object' == typeof exports ? t(require('react')) : t(e.react);
 // for (var n in r) ('object' == typeof exports ? exports : e)[n] = r[n];
 // }
-fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
-  match stmts.get(0) {
+fn is_umd_checks(stmts: &[Stmt]) -> bool {
+  match stmts.first() {
     Some(stmt) => match stmt {
       // TODO: handle ternary version
       // !(function (e, t) {
@@ -1742,13 +2804,13 @@ fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
         Expr::Cond(CondExpr { test, alt, .. }) => match &**test {
           Expr::Bin(BinExpr { left, right, op, .. }) => {
             if matches!(op, BinaryOp::LogicalAnd) {
-              if (is_umd_exports_check(&left) && is_umd_module_check(&right))
-                || (is_umd_exports_check(&right) && is_umd_module_check(&left))
+              if (is_umd_exports_check(left) && is_umd_module_check(right))
+                || (is_umd_exports_check(right) && is_umd_module_check(left))
               {
                 match &**alt {
                   Expr::Cond(CondExpr { test, .. }) => match &**test {
                     Expr::Bin(BinExpr { left, op, .. }) => {
-                      if matches!(op, BinaryOp::LogicalAnd) && is_umd_define_check(&left) {
+                      if matches!(op, BinaryOp::LogicalAnd) && is_umd_define_check(left) {
                         return true;
                       }
                       return false;
@@ -1760,7 +2822,7 @@ fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
               }
               return false;
             }
-            return false;
+            false
           }
           _ => false,
         },
@@ -1769,14 +2831,14 @@ fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
       Stmt::If(IfStmt { test, alt, .. }) => match &**test {
         Expr::Bin(BinExpr { left, right, op, .. }) => {
           if matches!(op, BinaryOp::LogicalAnd) {
-            if (is_umd_exports_check(&left) && is_umd_module_check(&right))
-              || (is_umd_exports_check(&right) && is_umd_module_check(&left))
+            if (is_umd_exports_check(left) && is_umd_module_check(right))
+              || (is_umd_exports_check(right) && is_umd_module_check(left))
             {
-              match &*alt {
+              match alt {
                 Some(alt_stmt) => match &**alt_stmt {
                   Stmt::If(IfStmt { test, .. }) => match &**test {
                     Expr::Bin(BinExpr { left, op, .. }) => {
-                      if matches!(op, BinaryOp::LogicalAnd) && is_umd_define_check(&left) {
+                      if matches!(op, BinaryOp::LogicalAnd) && is_umd_define_check(left) {
                         return true;
                       }
                       return false;
@@ -1790,7 +2852,7 @@ fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
             }
             return false;
           }
-          return false;
+          false
         }
         _ => false,
       },
@@ -1800,7 +2862,20 @@ fn is_umd_checks(stmts: &Vec<Stmt>) -> bool {
   }
 }
 
-fn get_iife_body(call: &CallExpr) -> Option<Vec<Stmt>> {
+pub(crate) fn get_iife_body(call: &CallExpr) -> Option<Body<'_>> {
+  match get_iife_fn(call)? {
+    Expr::Fn(func) => func
+      .function
+      .body
+      .as_ref()
+      .map(|BlockStmt { stmts, .. }| Body::Stmts(stmts)),
+    Expr::Arrow(arrow) => Some(get_arrow_body(arrow)),
+    _ => None,
+  }
+}
+
+// get the function or arrow function that is called by an IIFE
+pub(crate) fn get_iife_fn(call: &CallExpr) -> Option<&Expr> {
   let expr = if let Some(callee) = with_expr_callee(call) {
     match callee {
       Expr::Paren(ParenExpr { expr, .. }) => expr.as_ref(),
@@ -1825,107 +2900,326 @@ fn get_iife_body(call: &CallExpr) -> Option<Vec<Stmt>> {
     return None;
   };
   match expr {
-    Expr::Fn(func) => {
-      if let Some(BlockStmt { stmts, .. }) = &func.function.body {
-        return Some(stmts.clone());
-      }
-    }
-    Expr::Arrow(arrow) => return Some(get_arrow_body_as_stmts(arrow)),
-    _ => {}
+    Expr::Fn(_) | Expr::Arrow(_) => Some(expr),
+    _ => None,
   }
-  None
 }
 
-fn is_export_call(call: &CallExpr) -> bool {
-  if let Some(callee) = with_expr_callee(call) {
-    match callee {
-      Expr::Ident(id) => {
-        return id.sym.as_ref().eq("__export");
-      }
-      _ => {}
-    }
+fn is_constructible_function(function: &Function) -> bool {
+  !function.is_async && !function.is_generator
+}
+
+// match `__export(...)`, `__reExport(...)` or `__toCommonJS(...)`
+fn is_helper_call(call: &CallExpr, helper: &str) -> bool {
+  if let Some(Expr::Ident(id)) = with_expr_callee(call) {
+    return id.sym.as_ref().eq(helper);
   }
   false
 }
 
-// match:
-// require("tslib").__exportStar(..., exports)
-// (0, require("tslib").__exportStar)(..., exports)
-// const tslib = require("tslib"); (0, tslib.__exportStar)(..., exports)
-// const {__exportStar} = require("tslib"); (0, __exportStar)(..., exports)
-// const __exportStar = () => {}; __exportStar(..., exports)
-fn is_tslib_export_star_call(call: &CallExpr) -> bool {
-  if let Some(callee) = with_expr_callee(call) {
-    match callee {
-      Expr::Member(MemberExpr { prop, .. }) => {
-        if let MemberProp::Ident(prop) = prop {
-          return prop.sym.as_ref().eq("__exportStar");
+// System.register([...], function (...) { ... })
+fn is_system_register_call(call: &CallExpr) -> bool {
+  matches!(with_expr_callee(call), Some(expr) if is_member(expr, "System", "register"))
+    && !call.args.is_empty()
+    && call.args.iter().all(|arg| arg.spread.is_none())
+}
+
+fn get_fn_params_and_body(expr: &Expr) -> Option<(Vec<&Pat>, Body<'_>)> {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => get_fn_params_and_body(expr),
+    Expr::Fn(FnExpr { function, .. }) => Some((
+      function.params.iter().map(|param| &param.pat).collect(),
+      Body::Stmts(&function.body.as_ref()?.stmts),
+    )),
+    Expr::Arrow(arrow) => Some((arrow.params.iter().collect(), get_arrow_body(arrow))),
+    _ => None,
+  }
+}
+
+// get the object that the loop copies the keys to, e.g. `_exportObj[_key] = _dep[_key]`
+fn get_key_assign_target(stmt: &Stmt, key: &str) -> Option<String> {
+  match stmt {
+    Stmt::Expr(ExprStmt { expr, .. }) => get_key_assign_target_from_expr(expr, key),
+    Stmt::If(IfStmt { cons, .. }) => get_key_assign_target(cons, key),
+    Stmt::Block(BlockStmt { stmts, .. }) => stmts.iter().find_map(|stmt| get_key_assign_target(stmt, key)),
+    _ => None,
+  }
+}
+
+fn get_key_assign_target_from_expr(expr: &Expr, key: &str) -> Option<String> {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => get_key_assign_target_from_expr(expr, key),
+    Expr::Assign(AssignExpr {
+      left:
+        AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+          obj,
+          prop: MemberProp::Computed(ComputedPropName { expr, .. }),
+          ..
+        })),
+      ..
+    }) => match (obj.as_ref(), expr.as_ref()) {
+      (Expr::Ident(obj), Expr::Ident(prop)) if prop.sym.as_ref().eq(key) => Some(obj.sym.as_ref().to_owned()),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+// for (var key in obj)
+// check if the test only passes for some values of the key, e.g. `key === "foo" || key === "bar"`
+fn is_key_equality(test: &Expr, key: &str) -> bool {
+  match test {
+    Expr::Paren(ParenExpr { expr, .. }) => is_key_equality(expr, key),
+    Expr::Bin(BinExpr {
+      op: BinaryOp::EqEq | BinaryOp::EqEqEq,
+      left,
+      right,
+      ..
+    }) => {
+      let is_key = |expr: &Expr| matches!(expr, Expr::Ident(id) if id.sym.as_ref().eq(key));
+      (is_key(left) && matches!(right.as_ref(), Expr::Lit(_)))
+        || (is_key(right) && matches!(left.as_ref(), Expr::Lit(_)))
+    }
+    Expr::Bin(BinExpr {
+      op: BinaryOp::LogicalAnd,
+      left,
+      right,
+      ..
+    }) => is_key_equality(left, key) || is_key_equality(right, key),
+    Expr::Bin(BinExpr {
+      op: BinaryOp::LogicalOr,
+      left,
+      right,
+      ..
+    }) => is_key_equality(left, key) && is_key_equality(right, key),
+    _ => false,
+  }
+}
+
+fn get_for_in_key(stmt: &ForInStmt) -> Option<&Ident> {
+  get_for_head_ident(&stmt.left)
+}
+
+// for (var key of arr)
+fn get_for_head_ident(head: &ForHead) -> Option<&Ident> {
+  match head {
+    ForHead::VarDecl(var) if var.decls.len() == 1 => match &var.decls[0].name {
+      Pat::Ident(BindingIdent { id, .. }) => Some(id),
+      _ => None,
+    },
+    ForHead::Pat(pat) => match pat.as_ref() {
+      Pat::Ident(BindingIdent { id, .. }) => Some(id),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+fn is_webpack_getters_call(call: &CallExpr, runtimes: &HashSet<String>) -> bool {
+  !get_webpack_getters(call, runtimes).is_empty()
+}
+
+// get the names that are defined by the webpack runtime helpers:
+// __webpack_require__.r(__webpack_exports__)
+// __webpack_require__.d(__webpack_exports__, { foo: () => foo })
+// __webpack_require__.d(__webpack_exports__, "foo", function() { return foo })
+fn get_webpack_getters(call: &CallExpr, runtimes: &HashSet<String>) -> Vec<(String, Provenance)> {
+  let Some(Expr::Member(MemberExpr {
+    obj,
+    prop: MemberProp::Ident(prop),
+    ..
+  })) = with_expr_callee(call)
+  else {
+    return vec![];
+  };
+  let is_runtime = match obj.as_ref() {
+    Expr::Ident(id) => id.sym.as_ref().eq("__webpack_require__") || runtimes.contains(id.sym.as_ref()),
+    _ => false,
+  };
+  if !is_runtime || call.args.iter().any(|arg| arg.spread.is_some()) {
+    return vec![];
+  }
+  let is_getter = |expr: &Expr| matches!(expr, Expr::Fn(_) | Expr::Arrow(_));
+  match (prop.sym.as_ref(), call.args.as_slice()) {
+    ("r", [_]) => vec![("__esModule".to_owned(), Provenance::WebpackNamespace)],
+    ("d", [_, definition]) => match definition.expr.as_ref() {
+      Expr::Object(ObjectLit { props, .. }) if !props.is_empty() => {
+        let all_getters = props.iter().all(|prop| match prop {
+          PropOrSpread::Prop(prop) => match prop.as_ref() {
+            Prop::KeyValue(KeyValueProp { value, .. }) => is_getter(value),
+            _ => false,
+          },
+          PropOrSpread::Spread(_) => false,
+        });
+        if !all_getters {
+          return vec![];
         }
+        get_obj_prop_names(props)
+          .into_iter()
+          .map(|name| (name, Provenance::WebpackDefine))
+          .collect()
       }
-      Expr::Ident(id) => {
-        return id.sym.as_ref().eq("__exportStar");
+      _ => vec![],
+    },
+    ("d", [_, name, getter]) => match (name.expr.as_ref(), getter.expr.as_ref()) {
+      (Expr::Lit(Lit::Str(Str { value, .. })), getter) if is_getter(getter) => {
+        vec![(value.to_string_lossy().into_owned(), Provenance::WebpackDefine)]
       }
-      Expr::Paren(ParenExpr { expr, .. }) => match expr.as_ref() {
-        Expr::Member(MemberExpr { prop, .. }) => {
-          if let MemberProp::Ident(prop) = prop {
-            return prop.sym.as_ref().eq("__exportStar");
-          }
-        }
-        Expr::Ident(id) => {
-          return id.sym.as_ref().eq("__exportStar");
+      _ => vec![],
+    },
+    _ => vec![],
+  }
+}
+
+// get the names that are defined on the webpack exports object that is returned by the bundle:
+// (() => { var __webpack_exports__ = {}; __webpack_require__.d(__webpack_exports__, { ... }); return __webpack_exports__; })()
+// (() => { ...; return (() => { o.r(n), o.d(n, { ... }); })(), n; })()
+fn get_returned_webpack_getters(expr: &Expr, runtimes: &HashSet<String>) -> Vec<(String, Provenance)> {
+  let mut getters = vec![];
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => return get_returned_webpack_getters(expr, runtimes),
+    Expr::Seq(SeqExpr { exprs, .. }) => {
+      if let Some((Expr::Ident(id), exprs)) = exprs.split_last().map(|(last, exprs)| (last.as_ref(), exprs)) {
+        for expr in exprs {
+          collect_webpack_getters_from_expr(expr, id.sym.as_ref(), runtimes, &mut getters);
         }
-        Expr::Seq(SeqExpr { exprs, .. }) => {
-          if let Some(last) = exprs.last() {
-            match last.as_ref() {
-              Expr::Member(MemberExpr { prop, .. }) => {
-                if let MemberProp::Ident(prop) = prop {
-                  return prop.sym.as_ref().eq("__exportStar");
-                }
-              }
-              Expr::Ident(id) => {
-                return id.sym.as_ref().eq("__exportStar");
-              }
-              _ => {}
-            }
+      }
+    }
+    Expr::Call(call) => {
+      if let Some(Body::Stmts(stmts)) = get_iife_body(call) {
+        if let Some(Stmt::Return(ReturnStmt { arg: Some(arg), .. })) = stmts.last() {
+          match arg.as_ref() {
+            Expr::Ident(id) => collect_webpack_getters_from_stmts(stmts, id.sym.as_ref(), runtimes, &mut getters),
+            arg => return get_returned_webpack_getters(arg, runtimes),
           }
         }
-        _ => {}
-      },
+      }
+    }
+    _ => {}
+  }
+  getters
+}
+
+// find the webpack getters that are defined on `name` by the statements, including the IIFEs in the statements
+fn collect_webpack_getters_from_stmts(
+  stmts: &[Stmt],
+  name: &str,
+  runtimes: &HashSet<String>,
+  getters: &mut Vec<(String, Provenance)>,
+) {
+  for stmt in stmts {
+    match stmt {
+      Stmt::Expr(ExprStmt { expr, .. }) => collect_webpack_getters_from_expr(expr, name, runtimes, getters),
+      Stmt::Return(ReturnStmt { arg: Some(arg), .. }) => {
+        collect_webpack_getters_from_expr(arg, name, runtimes, getters)
+      }
       _ => {}
     }
   }
-  false
 }
 
-fn get_member_expr_from_assign_target(v: &AssignTarget) -> Option<&MemberExpr> {
-  match v {
-    AssignTarget::Simple(s) => match s {
-      SimpleAssignTarget::Member(member) => Some(member),
+fn collect_webpack_getters_from_expr(
+  expr: &Expr,
+  name: &str,
+  runtimes: &HashSet<String>,
+  getters: &mut Vec<(String, Provenance)>,
+) {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => collect_webpack_getters_from_expr(expr, name, runtimes, getters),
+    Expr::Seq(SeqExpr { exprs, .. }) => {
+      for expr in exprs {
+        collect_webpack_getters_from_expr(expr, name, runtimes, getters);
+      }
+    }
+    Expr::Call(call) => {
+      if matches!(call.args.first().map(|arg| arg.expr.as_ref()), Some(Expr::Ident(id)) if id.sym.as_ref().eq(name)) {
+        getters.extend(get_webpack_getters(call, runtimes));
+      } else if let Some(Body::Stmts(stmts)) = get_iife_body(call) {
+        collect_webpack_getters_from_stmts(stmts, name, runtimes, getters);
+      }
+    }
+    _ => {}
+  }
+}
+
+// (this && this.__exportStar) || function (m, exports) { ... }
+fn get_this_helper_name(expr: &Expr) -> Option<&str> {
+  let Expr::Bin(BinExpr {
+    op: BinaryOp::LogicalOr,
+    left,
+    ..
+  }) = expr
+  else {
+    return None;
+  };
+  let left = match left.as_ref() {
+    Expr::Paren(ParenExpr { expr, .. }) => expr.as_ref(),
+    left => left,
+  };
+  match left {
+    Expr::Bin(BinExpr {
+      op: BinaryOp::LogicalAnd,
+      left,
+      right,
+      ..
+    }) if matches!(left.as_ref(), Expr::This(_)) => match right.as_ref() {
+      Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(prop),
+        ..
+      }) if matches!(obj.as_ref(), Expr::This(_)) => Some(prop.sym.as_ref()),
       _ => None,
     },
     _ => None,
   }
 }
 
-fn get_arrow_body_as_stmts(arrow: &ArrowExpr) -> Vec<Stmt> {
-  match &*arrow.body {
-    BlockStmtOrExpr::BlockStmt(BlockStmt { stmts, .. }) => stmts.clone(),
-    BlockStmtOrExpr::Expr(expr) => vec![Stmt::Return(ReturnStmt {
-      span: DUMMY_SP,
-      arg: Some(expr.clone()),
-    })],
+// get the returned value of a getter that only has a `return` statement
+// get: function () { return foo }
+// get: () => foo
+// get() { return foo }
+fn get_getter_return_value(prop: &Prop) -> Option<&Expr> {
+  match prop {
+    Prop::KeyValue(KeyValueProp { value, .. }) => get_fn_return_value(value),
+    Prop::Method(MethodProp { function, .. }) => get_body_return_value(Body::Stmts(&function.body.as_ref()?.stmts)),
+    // { get foo() { return foo } }
+    Prop::Getter(GetterProp { body, .. }) => get_body_return_value(Body::Stmts(&body.as_ref()?.stmts)),
+    _ => None,
   }
 }
 
-fn get_member_prop_name(expr: &Expr, obj_name: &str) -> Option<String> {
-  if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
-    if let Expr::Ident(obj) = obj.as_ref() {
-      if obj.sym.as_ref().eq(obj_name) {
-        return get_prop_name(prop);
-      }
-    }
+// get the returned value of a function that only has a `return` statement
+// function () { return foo }
+// () => foo
+fn get_fn_return_value(expr: &Expr) -> Option<&Expr> {
+  match expr {
+    Expr::Paren(ParenExpr { expr, .. }) => get_fn_return_value(expr),
+    Expr::Fn(FnExpr { function, .. }) => get_body_return_value(Body::Stmts(&function.body.as_ref()?.stmts)),
+    Expr::Arrow(arrow) => get_body_return_value(get_arrow_body(arrow)),
+    _ => None,
+  }
+}
+
+fn get_body_return_value(body: Body<'_>) -> Option<&Expr> {
+  match body {
+    Body::Stmts([Stmt::Return(ReturnStmt { arg: Some(arg), .. })]) => Some(arg),
+    Body::Expr(expr) => Some(expr),
+    _ => None,
+  }
+}
+
+fn get_member_expr_from_assign_target(v: &AssignTarget) -> Option<&MemberExpr> {
+  match v {
+    AssignTarget::Simple(SimpleAssignTarget::Member(member)) => Some(member),
+    _ => None,
+  }
+}
+
+fn get_arrow_body(arrow: &ArrowExpr) -> Body<'_> {
+  match arrow.body.as_ref() {
+    BlockStmtOrExpr::BlockStmt(BlockStmt { stmts, .. }) => Body::Stmts(stmts),
+    BlockStmtOrExpr::Expr(expr) => Body::Expr(expr),
   }
-  None
 }
 
 fn get_class_static_names(class: &Class) -> Vec<String> {
@@ -1968,6 +3262,42 @@ fn get_prop_name(prop: &MemberProp) -> Option<String> {
   }
 }
 
+// { foo, bar: () => bar, baz() {} }
+fn get_obj_prop_names(props: &[PropOrSpread]) -> Vec<String> {
+  props
+    .iter()
+    .filter_map(|prop| match prop {
+      PropOrSpread::Prop(prop) => match prop.as_ref() {
+        Prop::Shorthand(id) => Some(id.sym.as_ref().to_owned()),
+        Prop::KeyValue(KeyValueProp { key, .. }) | Prop::Method(MethodProp { key, .. }) => stringify_prop_name(key),
+        _ => None,
+      },
+      PropOrSpread::Spread(_) => None,
+    })
+    .collect()
+}
+
+// the own keys of an object, including the assigned properties
+fn get_obj_keys(desc: &ObjDesc) -> Vec<String> {
+  let mut keys = get_obj_prop_names(desc.props);
+  keys.extend(desc.extends.iter().cloned());
+  keys
+}
+
+// get the key and the local name of a destructured property, e.g. `{ foo }` or `{ foo: bar }`
+fn get_obj_pat_prop_binding(prop: &ObjectPatProp) -> Option<(String, String)> {
+  match prop {
+    ObjectPatProp::Assign(AssignPatProp { key, .. }) => {
+      Some((key.sym.as_ref().to_owned(), key.sym.as_ref().to_owned()))
+    }
+    ObjectPatProp::KeyValue(KeyValuePatProp { key, value, .. }) => match (stringify_prop_name(key), value.as_ref()) {
+      (Some(key), Pat::Ident(rename)) => Some((key, rename.id.sym.as_ref().to_owned())),
+      _ => None,
+    },
+    ObjectPatProp::Rest(_) => None,
+  }
+}
+
 fn stringify_prop_name(name: &PropName) -> Option<String> {
   match name {
     PropName::Ident(id) => Some(id.sym.as_ref().into()),
@@ -1984,11 +3314,3 @@ fn quote_ident(value: &str) -> Ident {
     optional: false,
   }
 }
-
-fn quote_str(value: &str) -> Str {
-  Str {
-    span: DUMMY_SP,
-    value: value.into(),
-    raw: None,
-  }
-}
@@ -1,39 +1,158 @@
+mod deps;
 mod error;
+mod format;
 mod lexer;
+mod result;
+mod scope;
 mod test;
 
-use crate::error::{DiagnosticBuffer, ErrorBuffer};
-use crate::lexer::ModuleLexer;
+use crate::deps::{get_require_calls, RequireCall};
+use crate::error::{get_syntax_error_code, ErrorBuffer};
+pub use crate::error::{Diagnostic, DiagnosticBuffer, Severity};
+use crate::format::ModuleSyntax;
+use crate::lexer::{get_mutated_arrays, get_webpack_runtimes, ModuleLexer};
+pub use crate::result::{
+  AnalyzeResult, DefaultKind, Export, MergedAnalyzeResult, MergedExport, MergedNamedReexport, MergedReexport,
+  ModuleFormat, NamedReexport, Provenance, Reexport, Require, SourceSpan, Warning, WarningKind,
+};
+use crate::scope::ScopedMap;
 
 use bytes_str::BytesStr;
-use indexmap::{IndexMap, IndexSet};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use swc_common::comments::SingleThreadedComments;
-use swc_common::errors::{Handler, HandlerFlags};
-use swc_common::{FileName, SourceMap};
-use swc_ecmascript::ast::{EsVersion, Module, Program};
-use swc_ecmascript::parser::{lexer::Lexer, EsSyntax, StringInput, Syntax};
-use swc_ecmascript::visit::FoldWith;
+use swc_common::errors::{DiagnosticId, Handler, HandlerFlags, SourceMapper};
+use swc_common::sync::Lrc;
+use swc_common::{FileName, SourceFile, SourceMap, Span, Spanned, DUMMY_SP};
+use swc_ecmascript::ast::{EsVersion, Expr, Lit, Module, ModuleItem, Number, Str, UnaryExpr, UnaryOp};
+use swc_ecmascript::parser::{error::Error, lexer::Lexer, EsSyntax, PResult, StringInput, Syntax};
+
+/// The target platform that `typeof` checks are evaluated for, e.g. `typeof window !== "undefined"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "camelCase"))]
+pub enum Platform {
+  Node,
+  Browser,
+  Worker,
+  Deno,
+}
+
+impl Platform {
+  /// get the `typeof` values of the globals for the platform.
+  fn globals(&self) -> &'static [(&'static str, &'static str)] {
+    match self {
+      Platform::Node => &[
+        ("window", "undefined"),
+        ("document", "undefined"),
+        ("self", "undefined"),
+        ("importScripts", "undefined"),
+        ("Deno", "undefined"),
+        ("process", "object"),
+        ("global", "object"),
+        ("globalThis", "object"),
+      ],
+      Platform::Browser => &[
+        ("window", "object"),
+        ("document", "object"),
+        ("self", "object"),
+        ("importScripts", "undefined"),
+        ("Deno", "undefined"),
+        ("process", "undefined"),
+        ("global", "undefined"),
+        ("globalThis", "object"),
+      ],
+      Platform::Worker => &[
+        ("window", "undefined"),
+        ("document", "undefined"),
+        ("self", "object"),
+        ("importScripts", "function"),
+        ("Deno", "undefined"),
+        ("process", "undefined"),
+        ("global", "undefined"),
+        ("globalThis", "object"),
+      ],
+      Platform::Deno => &[
+        ("window", "undefined"),
+        ("document", "undefined"),
+        ("self", "object"),
+        ("importScripts", "undefined"),
+        ("Deno", "object"),
+        ("process", "object"),
+        ("global", "object"),
+        ("globalThis", "object"),
+      ],
+    }
+  }
+}
+
+/// The CommonJS globals which are the same on every platform, this keeps the
+/// UMD checks (`typeof exports === "object"`, `typeof define === "function"`)
+/// selecting the CommonJS branch.
+const COMMONJS_GLOBALS: &[(&str, &str)] = &[
+  ("exports", "object"),
+  ("module", "object"),
+  ("require", "function"),
+  ("define", "undefined"),
+];
+
+/// The min number of times that the source is parsed again after blanking out the line of a fatal syntax error.
+const MIN_RECOVERY_ATTEMPTS: usize = 16;
+
+/// The max number of bytes that are parsed again to recover from fatal syntax errors, small modules get more attempts.
+/// The source from the line of the last error is dropped once the attempts run out.
+const MAX_RECOVERY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Options for analyzing a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalyzeOptions {
+  /// The value of `process.env.NODE_ENV`, default is "production".
+  pub node_env: String,
+  /// Whether to call `module.exports` if it is a function.
+  /// The result then describes the returned value instead of `module.exports`,
+  /// e.g. `default_kind` is `Object` for a function that returns an object.
+  pub call_mode: bool,
+  /// Replace global expressions with constant values, like esbuild's `--define`.
+  /// The keys are identifiers (`__DEV__`), member expressions (`process.env.BROWSER`)
+  /// or typeof expressions (`typeof window`), and the values are JS literals (`"true"`, `"'browser'"`).
+  pub defines: HashMap<String, String>,
+  /// The target platform, `typeof` checks of the platform globals are not evaluated if it's not set.
+  pub platform: Option<Platform>,
+}
+
+impl Default for AnalyzeOptions {
+  fn default() -> Self {
+    AnalyzeOptions {
+      node_env: "production".to_owned(),
+      call_mode: false,
+      defines: HashMap::new(),
+      platform: None,
+    }
+  }
+}
 
 pub struct CommonJSModuleLexer {
   pub module: Module,
+  source_map: Lrc<SourceMap>,
+  syntax: ModuleSyntax,
+  requires: Vec<RequireCall>,
+  webpack_runtimes: HashSet<String>,
+  mutated_arrays: HashSet<String>,
 }
 
 impl CommonJSModuleLexer {
   /// parse the module from the source code.
+  ///
+  /// The source is parsed as a commonjs function body (sloppy mode, top-level `return` is allowed),
+  /// and falls back to an ES module if that fails, e.g. the source has `import` declarations.
   pub fn init(specifier: &str, source: &str) -> Result<Self, DiagnosticBuffer> {
-    let source_map = SourceMap::default();
+    let source_map: Lrc<SourceMap> = Default::default();
     let source_file = source_map.new_source_file(
       FileName::Real(Path::new(specifier).to_path_buf()).into(),
       BytesStr::from_str_slice(source),
     );
-    let sm = &source_map;
+    let sm = source_map.clone();
     let error_buffer = ErrorBuffer::new(specifier);
-    let syntax = Syntax::Es(EsSyntax::default());
-    let input = StringInput::from(&*source_file);
-    let comments = SingleThreadedComments::default();
-    let lexer = Lexer::new(syntax, EsVersion::Es2020, input, Some(&comments));
-    let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
     let handler = Handler::with_emitter_and_flags(
       Box::new(error_buffer.clone()),
       HandlerFlags {
@@ -42,30 +161,383 @@ impl CommonJSModuleLexer {
         ..HandlerFlags::default()
       },
     );
-    let module = parser.parse_module().map_err(move |err| {
-      let mut diagnostic = err.into_diagnostic(&handler);
-      diagnostic.emit();
+    let (module, _) = parse(&source_file).map_err(move |err| {
+      emit_error(err, &handler);
       DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo))
     })?;
-    Ok(CommonJSModuleLexer { module })
+    Ok(Self::new(module, source_map))
+  }
+
+  /// parse the module from the source code, recovering from syntax errors.
+  ///
+  /// The recovered AST of the parser is used when possible, and the line of a fatal syntax error is
+  /// blanked out before the source is parsed again, an unterminated statement at the end of the source
+  /// is blanked out as a whole. If there are too many fatal errors, the source from the line of the last one
+  /// is dropped and the module keeps what parsed before it. The syntax errors are returned with the lexer,
+  /// the module is empty if nothing could be recovered.
+  pub fn init_recoverable(specifier: &str, source: &str) -> (Self, DiagnosticBuffer) {
+    let file_name: Lrc<FileName> = FileName::Real(Path::new(specifier).to_path_buf()).into();
+    let error_buffer = ErrorBuffer::new(specifier);
+    let handler = Handler::with_emitter_and_flags(
+      Box::new(error_buffer.clone()),
+      HandlerFlags {
+        can_emit_warnings: true,
+        dont_buffer_diagnostics: true,
+        ..HandlerFlags::default()
+      },
+    );
+    let max_attempts = (MAX_RECOVERY_BYTES / source.len().max(1)).max(MIN_RECOVERY_ATTEMPTS);
+    let mut source = source.to_owned();
+    let mut module = None;
+    let mut source_map: Lrc<SourceMap>;
+    let mut attempts = 0;
+    let mut truncated = false;
+    loop {
+      // the blanked source has the same byte offsets, so the spans of the previous attempts are still valid
+      source_map = Default::default();
+      let source_file = source_map.new_source_file(file_name.clone(), BytesStr::from(source.clone()));
+      match parse(&source_file) {
+        Ok((parsed, errors)) => {
+          for err in errors {
+            emit_error(err, &handler);
+          }
+          module = Some(parsed);
+          break;
+        }
+        Err(err) => {
+          let pos = (err.span().lo.0 - source_file.start_pos.0) as usize;
+          // the errors after truncating the source come from the dropped part
+          if !truncated {
+            emit_error(err, &handler);
+          }
+          attempts += 1;
+          // an error at the end of the source comes from an unterminated statement, blanking its last lines
+          // one by one would eat the whole module, so drop the statement and keep what parsed before it
+          let recovered = if pos >= source.trim_end().len() {
+            blank_last_statement(&mut source)
+          } else if attempts < max_attempts {
+            blank_line(&mut source, pos)
+          } else {
+            // out of attempts, keep what parsed before the line of the error and drop the rest
+            truncated = true;
+            blank_from_line(&mut source, pos)
+          };
+          if !recovered {
+            break;
+          }
+        }
+      }
+    }
+    let module = module.unwrap_or(Module {
+      span: DUMMY_SP,
+      body: vec![],
+      shebang: None,
+    });
+    let diagnostics = DiagnosticBuffer::from_error_buffer(error_buffer, |span| source_map.lookup_char_pos(span.lo));
+    (Self::new(module, source_map), diagnostics)
+  }
+
+  fn new(module: Module, source_map: Lrc<SourceMap>) -> Self {
+    let syntax = ModuleSyntax::from_module(&module);
+    let requires = get_require_calls(&module);
+    let webpack_runtimes = get_webpack_runtimes(&module);
+    let mutated_arrays = get_mutated_arrays(&module);
+    CommonJSModuleLexer {
+      module,
+      source_map,
+      syntax,
+      requires,
+      webpack_runtimes,
+      mutated_arrays,
+    }
   }
 
   /// get named exports and reexports of the module.
-  pub fn analyze(&self, node_env: &str, call_mode: bool) -> (Vec<String>, Vec<String>) {
-    let mut lexer = ModuleLexer {
-      call_mode,
+  pub fn analyze(&self, node_env: &str, call_mode: bool) -> AnalyzeResult {
+    self.analyze_with_options(&AnalyzeOptions {
       node_env: node_env.to_owned(),
+      call_mode,
+      ..AnalyzeOptions::default()
+    })
+  }
+
+  /// analyze the module with several configurations, e.g. `development` and `production`,
+  /// and merge the results. The module is parsed only once.
+  pub fn analyze_all(&self, options: &[AnalyzeOptions]) -> MergedAnalyzeResult {
+    let mut results: Vec<AnalyzeResult> = Vec::with_capacity(options.len());
+    for (index, opts) in options.iter().enumerate() {
+      // reuse the result of the same configuration
+      if let Some(same) = options[..index].iter().position(|o| o == opts) {
+        results.push(results[same].clone());
+      } else {
+        results.push(self.analyze_with_options(opts));
+      }
+    }
+    MergedAnalyzeResult::new(results)
+  }
+
+  /// get named exports and reexports of the module with the given options.
+  pub fn analyze_with_options(&self, options: &AnalyzeOptions) -> AnalyzeResult {
+    let mut defines = HashMap::new();
+    let mut warnings = vec![];
+    let mut keys = options.defines.keys().collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+      let value = &options.defines[key];
+      match parse_define_value(value) {
+        Some(lit) => {
+          defines.insert(key.to_owned(), lit);
+        }
+        None => warnings.push(Warning {
+          kind: WarningKind::InvalidDefine,
+          message: format!(
+            "could not parse {} {}: {}",
+            WarningKind::InvalidDefine.description(),
+            key,
+            value
+          ),
+          span: SourceSpan::default(),
+        }),
+      }
+    }
+    defines
+      .entry("process.env.NODE_ENV".to_owned())
+      .or_insert_with(|| Lit::Str(Str::from(options.node_env.as_str())));
+    if let Some(platform) = options.platform {
+      for (name, value) in platform.globals().iter().chain(COMMONJS_GLOBALS) {
+        defines
+          .entry(format!("typeof {}", name))
+          .or_insert_with(|| Lit::Str(Str::from(*value)));
+      }
+    }
+    let mut lexer = ModuleLexer {
+      call_mode: options.call_mode,
+      defines,
       fn_returned: false,
-      idents: IndexMap::new(),
-      exports_alias: IndexSet::new(),
-      named_exports: IndexSet::new(),
-      reexports: IndexSet::new(),
+      idents: ScopedMap::default(),
+      exports_alias: ScopedMap::default(),
+      named_exports: IndexMap::new(),
+      reexports: vec![],
+      named_reexports: IndexMap::new(),
+      span: DUMMY_SP,
+      annotation: false,
+      default_kind: DefaultKind::Object,
+      constructible: false,
+      warnings: vec![],
+      webpack_runtimes: &self.webpack_runtimes,
+      mutated_arrays: &self.mutated_arrays,
     };
-    let program = Program::Module(self.module.clone());
-    program.fold_with(&mut lexer);
-    (
-      lexer.named_exports.into_iter().collect(),
-      lexer.reexports.into_iter().collect(),
-    )
+    lexer.walk_module(&self.module);
+    let es_module = lexer.named_exports.contains_key("__esModule");
+    AnalyzeResult {
+      exports: lexer
+        .named_exports
+        .into_iter()
+        .map(|(name, (span, provenance))| Export {
+          name,
+          provenance,
+          span: self.source_span(span),
+        })
+        .collect(),
+      reexports: lexer
+        .reexports
+        .into_iter()
+        .map(|(reexport, span, provenance)| Reexport {
+          specifier: reexport.specifier,
+          called: reexport.called,
+          member: reexport.member,
+          extends: reexport.extends,
+          provenance,
+          span: self.source_span(span),
+        })
+        .collect(),
+      named_reexports: lexer
+        .named_reexports
+        .into_iter()
+        .map(|(name, (reexport, span, provenance))| NamedReexport {
+          name,
+          specifier: reexport.specifier,
+          member: reexport.member,
+          provenance,
+          span: self.source_span(span),
+        })
+        .collect(),
+      default_kind: lexer.default_kind,
+      callable: matches!(lexer.default_kind, DefaultKind::Function),
+      constructible: lexer.constructible,
+      es_module,
+      warnings: warnings
+        .into_iter()
+        .chain(lexer.warnings.into_iter().map(|(kind, span)| {
+          let span = self.source_span(span);
+          Warning {
+            kind,
+            message: format!(
+              "could not resolve {} at {}:{}",
+              kind.description(),
+              span.line,
+              span.column
+            ),
+            span,
+          }
+        }))
+        .collect(),
+      format: self.syntax.format,
+      esm_exports: self.syntax.esm_exports.clone(),
+      esm_reexports: self.syntax.esm_reexports.clone(),
+      requires: self.requires.iter().map(|call| self.to_require(call)).collect(),
+    }
+  }
+
+  fn to_require(&self, call: &RequireCall) -> Require {
+    let (specifier, dynamic) = match &call.specifier {
+      Some(specifier) => (specifier.clone(), false),
+      None => (self.source_map.span_to_snippet(call.arg_span).unwrap_or_default(), true),
+    };
+    Require {
+      specifier,
+      dynamic,
+      conditional: call.conditional,
+      in_try: call.in_try,
+      span: self.source_span(call.span),
+    }
+  }
+
+  fn source_span(&self, span: Span) -> SourceSpan {
+    if span.is_dummy() {
+      return SourceSpan::default();
+    }
+    let loc = self.source_map.lookup_char_pos(span.lo);
+    SourceSpan {
+      start: span.lo.0 - loc.file.start_pos.0,
+      end: span.hi.0 - loc.file.start_pos.0,
+      line: loc.line,
+      column: loc.col_display,
+    }
+  }
+}
+
+// parse the source as commonjs, or as an ES module if that fails, returns the module and the recovered errors
+fn parse(source_file: &SourceFile) -> PResult<(Module, Vec<Error>)> {
+  parse_commonjs(source_file).or_else(|err| parse_module(source_file).map_err(|_| err))
+}
+
+// parse the source as the body of a commonjs module wrapper function
+fn parse_commonjs(source_file: &SourceFile) -> PResult<(Module, Vec<Error>)> {
+  let comments = SingleThreadedComments::default();
+  let lexer = Lexer::new(
+    Syntax::Es(EsSyntax {
+      allow_return_outside_function: true,
+      ..EsSyntax::default()
+    }),
+    EsVersion::Es2020,
+    StringInput::from(source_file),
+    Some(&comments),
+  );
+  let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
+  let script = parser.parse_commonjs()?;
+  let module = Module {
+    span: script.span,
+    body: script.body.into_iter().map(ModuleItem::Stmt).collect(),
+    shebang: script.shebang,
+  };
+  Ok((module, parser.take_errors()))
+}
+
+fn parse_module(source_file: &SourceFile) -> PResult<(Module, Vec<Error>)> {
+  let comments = SingleThreadedComments::default();
+  let lexer = Lexer::new(
+    Syntax::Es(EsSyntax::default()),
+    EsVersion::Es2020,
+    StringInput::from(source_file),
+    Some(&comments),
+  );
+  let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
+  let module = parser.parse_module()?;
+  Ok((module, parser.take_errors()))
+}
+
+// emit the syntax error with the name of its kind as the error code, e.g. `TS1109`
+fn emit_error(err: Error, handler: &Handler) {
+  let code = get_syntax_error_code(err.kind()).to_owned();
+  let mut diagnostic = err.into_diagnostic(handler);
+  diagnostic.code(DiagnosticId::Error(code));
+  diagnostic.emit();
+}
+
+// replace the line at the byte position with spaces, returns `false` if there is nothing to blank out
+fn blank_line(source: &mut String, pos: usize) -> bool {
+  let pos = pos.min(source.len());
+  let start = source[..pos].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+  let end = source[pos..].find(['\n', '\r']).map_or(source.len(), |i| pos + i);
+  blank_range(source, start, end)
+}
+
+// replace the source from the start of the last top-level statement with spaces, returns `false` if there is none
+fn blank_last_statement(source: &mut String) -> bool {
+  let end = source.trim_end().len();
+  let start = source[..end]
+    .char_indices()
+    .rev()
+    .find(|&(i, c)| {
+      i > 0 && matches!(source.as_bytes()[i - 1], b'\n' | b'\r') && !c.is_whitespace() && !matches!(c, '}' | ')' | ']')
+    })
+    .map(|(i, _)| i);
+  match start {
+    Some(start) => blank_range(source, start, source.len()),
+    None => false,
+  }
+}
+
+// replace the source from the start of the line at the byte position with spaces
+fn blank_from_line(source: &mut String, pos: usize) -> bool {
+  let pos = pos.min(source.len());
+  let start = source[..pos].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+  blank_range(source, start, source.len())
+}
+
+// replace the source in the byte range with spaces, returns `false` if there is nothing to blank out
+fn blank_range(source: &mut String, start: usize, end: usize) -> bool {
+  if source[start..end].trim().is_empty() {
+    return false;
+  }
+  // keep the line breaks and the byte length, so the spans of the rest of the source don't change
+  let blank: String = source[start..end]
+    .chars()
+    .map(|c| match c {
+      '\n' | '\r' => c.to_string(),
+      _ => " ".repeat(c.len_utf8()),
+    })
+    .collect();
+  source.replace_range(start..end, &blank);
+  true
+}
+
+// parse the value of a define, e.g. `"production"`, `true`, `1`, `null`
+fn parse_define_value(value: &str) -> Option<Lit> {
+  let source_map = SourceMap::default();
+  let source_file = source_map.new_source_file(FileName::Anon.into(), BytesStr::from_str_slice(value));
+  let lexer = Lexer::new(
+    Syntax::Es(EsSyntax::default()),
+    EsVersion::Es2020,
+    StringInput::from(&*source_file),
+    None,
+  );
+  let expr = swc_ecmascript::parser::Parser::new_from(lexer).parse_expr().ok()?;
+  match *expr {
+    Expr::Lit(lit) => match lit {
+      Lit::Str(_) | Lit::Num(_) | Lit::Bool(_) | Lit::Null(_) => Some(lit),
+      _ => None,
+    },
+    // -1
+    Expr::Unary(UnaryExpr {
+      op: UnaryOp::Minus,
+      arg,
+      ..
+    }) => match *arg {
+      Expr::Lit(Lit::Num(Number { value, .. })) => Some(Lit::Num(Number::from(-value))),
+      _ => None,
+    },
+    _ => None,
   }
 }
@@ -0,0 +1,379 @@
+/// The location of a statement in the source code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SourceSpan {
+  /// The byte offset of the start of the statement.
+  pub start: u32,
+  /// The byte offset of the end of the statement.
+  pub end: u32,
+  /// The (1-based) line number of the start of the statement.
+  pub line: usize,
+  /// The (0-based) column of the start of the statement.
+  pub column: usize,
+}
+
+/// The recognized pattern that produced an export or reexport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum Provenance {
+  /// `exports.foo = ...` or `module.exports.foo = ...`
+  Assignment,
+  /// `exports.foo || (exports.foo = {})`
+  LogicalAssignment,
+  /// `["foo", "bar"].forEach((key) => exports[key] = ...)` or a `for...of`/`for...in` loop over a known array or object
+  LoopAssignment,
+  /// `Object.defineProperty(exports, "foo", { ... })` or `Reflect.defineProperty(exports, "foo", { ... })`
+  DefineProperty,
+  /// `Object.defineProperties(exports, { foo: { ... } })`
+  DefineProperties,
+  /// `Object.assign(exports, { foo })`
+  ObjectAssign,
+  /// `module.exports = { foo }`
+  ObjectLiteral,
+  /// `module.exports = class { static foo = ... }`
+  ClassStatic,
+  /// `function fn() {}; fn.foo = ...; module.exports = fn`
+  FunctionProperty,
+  /// `module.exports = require("lib")`
+  ModuleExports,
+  /// `Object.setPrototypeOf(module.exports, require("lib"))`
+  SetPrototypeOf,
+  /// `var lib = require("lib"); lib.foo = ...; module.exports = lib`
+  ReexportProperty,
+  /// `__exportStar(require("lib"), exports)` or its `for (var p in m)` loop
+  TslibExportStar,
+  /// `Object.keys(_lib).forEach(function (key) { exports[key] = _lib[key] })`, emitted by Babel and Rollup
+  BabelExportStar,
+  /// `__createBinding(exports, require("lib"), "foo")`
+  CreateBinding,
+  /// `__export({ foo })` or `__export(require("lib"))`
+  ExportHelper,
+  /// `0 && (module.exports = { foo })`
+  EsbuildAnnotation,
+  /// `__export(src_exports, { foo: () => foo }); module.exports = __toCommonJS(src_exports)`
+  EsbuildExport,
+  /// `__reExport(src_exports, require("lib"), module.exports)`
+  EsbuildReexport,
+  /// `_export(exports, { foo: function() { return foo; } })`
+  SwcExport,
+  /// `_export_star(require("lib"), exports)`
+  SwcExportStar,
+  /// `$parcel$export(module.exports, "foo", () => foo)`
+  ParcelExport,
+  /// `$parcel$exportWildcard(module.exports, require("lib"))`
+  ParcelExportWildcard,
+  /// `_export("foo", foo)` in a `System.register` module
+  SystemExport,
+  /// `__webpack_require__.r(exports)`
+  WebpackNamespace,
+  /// `__webpack_require__.d(exports, { foo: ... })`
+  WebpackDefine,
+  /// `exports.default = ...` in a webpack 4 module
+  WebpackDefault,
+}
+
+/// A named export of the module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Export {
+  pub name: String,
+  /// The pattern that produced the export.
+  pub provenance: Provenance,
+  /// The span of the statement that produced the export.
+  pub span: SourceSpan,
+}
+
+/// A named export that is bound to a member of another module, e.g. `export { foo } from "./foo"` in ESM.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NamedReexport {
+  pub name: String,
+  pub specifier: String,
+  /// The member of the module, `None` if the module itself is exported, e.g. `export * as foo from "./foo"`.
+  pub member: Option<String>,
+  /// The pattern that produced the reexport.
+  pub provenance: Provenance,
+  /// The span of the statement that produced the reexport.
+  pub span: SourceSpan,
+}
+
+/// A module that is reexported by the module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Reexport {
+  pub specifier: String,
+  /// Whether the module is called, e.g. `module.exports = require("lib")()`.
+  pub called: bool,
+  /// The member that is accessed, e.g. `module.exports = require("lib").default`.
+  pub member: Option<String>,
+  /// The extra names that are assigned to the reexported module.
+  /// ```js
+  /// var lib = require("lib");
+  /// lib.bar = true;
+  /// module.exports = lib;
+  /// ```
+  pub extends: Vec<String>,
+  /// The pattern that produced the reexport.
+  pub provenance: Provenance,
+  /// The span of the statement that produced the reexport.
+  pub span: SourceSpan,
+}
+
+/// The kind of an export pattern that could not be resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum WarningKind {
+  /// `exports[name] = ...` with a non-constant key
+  DynamicAssignment,
+  /// `Object.defineProperty(exports, name, { ... })` with a non-constant key
+  DynamicDefineProperty,
+  /// `module.exports = foo()` with an unknown value
+  UnknownModuleExports,
+  /// a define whose value is not a JS literal, the define is ignored
+  InvalidDefine,
+}
+
+impl WarningKind {
+  pub(crate) fn description(&self) -> &'static str {
+    match self {
+      WarningKind::DynamicAssignment => "the dynamic export assignment",
+      WarningKind::DynamicDefineProperty => "the dynamic export property definition",
+      WarningKind::UnknownModuleExports => "the value assigned to module.exports",
+      WarningKind::InvalidDefine => "the value of the define",
+    }
+  }
+}
+
+/// An export pattern that the lexer saw but could not resolve, the exports may be incomplete,
+/// or an option that could not be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Warning {
+  pub kind: WarningKind,
+  /// e.g. "could not resolve the dynamic export assignment at 3:2"
+  pub message: String,
+  /// The span of the unresolved expression, empty for an invalid define.
+  pub span: SourceSpan,
+}
+
+/// A `require()` call that is found anywhere in the module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct Require {
+  /// The specifier, or the source code of the argument if it's dynamic, e.g. `"./locale/" + lang`.
+  pub specifier: String,
+  /// Whether the argument is not a constant string.
+  pub dynamic: bool,
+  /// Whether the call is not executed unconditionally when the module is loaded,
+  /// e.g. in an `if` branch, a logical expression or a function.
+  pub conditional: bool,
+  /// Whether the call is in a `try` block.
+  pub in_try: bool,
+  /// The span of the call.
+  pub span: SourceSpan,
+}
+
+/// What `module.exports` is bound to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum DefaultKind {
+  /// `module.exports = { foo }`, or the `exports` object that is not replaced.
+  #[default]
+  Object,
+  /// `module.exports = function () {}`
+  Function,
+  /// `module.exports = class {}`
+  Class,
+  /// `module.exports = "foo"`
+  Primitive,
+  /// `module.exports = require("lib")`
+  Reexport,
+  /// `module.exports = foo()`
+  Unknown,
+}
+
+/// The module format that is detected from the syntax of the module.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum ModuleFormat {
+  /// uses `require`, `module` or `exports`
+  Cjs,
+  /// has `import`/`export` statements or `import.meta`
+  Esm,
+  /// has ESM syntax and uses the commonjs globals
+  Mixed,
+  /// calls `define(...)` at the top level
+  Amd,
+  /// calls `System.register(...)` at the top level
+  System,
+  /// neither ESM syntax nor commonjs globals are found
+  #[default]
+  Unknown,
+}
+
+/// The result of analyzing a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnalyzeResult {
+  pub exports: Vec<Export>,
+  pub reexports: Vec<Reexport>,
+  /// The named exports that are bound to a member of another module.
+  pub named_reexports: Vec<NamedReexport>,
+  /// What `module.exports` is bound to, or the value returned by calling it in call mode.
+  pub default_kind: DefaultKind,
+  /// Whether `module.exports` (the returned value in call mode) can be called.
+  pub callable: bool,
+  /// Whether `module.exports` (the returned value in call mode) can be called with `new`.
+  pub constructible: bool,
+  /// Whether `module.exports.__esModule` is set.
+  pub es_module: bool,
+  /// The export patterns that could not be resolved.
+  pub warnings: Vec<Warning>,
+  /// The module format that is detected from the syntax of the module.
+  pub format: ModuleFormat,
+  /// The names that are exported by the ESM `export` statements.
+  pub esm_exports: Vec<String>,
+  /// The sources of the ESM `export * from "..."` statements.
+  pub esm_reexports: Vec<String>,
+  /// Every `require()` call of the module, including the calls in functions and conditional branches.
+  pub requires: Vec<Require>,
+}
+
+impl AnalyzeResult {
+  /// get the names of the named exports.
+  pub fn export_names(&self) -> Vec<String> {
+    self.exports.iter().map(|export| export.name.clone()).collect()
+  }
+
+  /// get the specifiers of the reexports.
+  pub fn reexport_specifiers(&self) -> Vec<String> {
+    self
+      .reexports
+      .iter()
+      .map(|reexport| reexport.specifier.clone())
+      .collect()
+  }
+
+  /// get the named reexports as `name:specifier:member`, the member is `*` if the whole module is reexported.
+  pub fn named_reexport_triples(&self) -> Vec<String> {
+    self
+      .named_reexports
+      .iter()
+      .map(|r| format!("{}:{}:{}", r.name, r.specifier, r.member.as_deref().unwrap_or("*")))
+      .collect()
+  }
+}
+
+/// A named export that is found in some of the analyzed configurations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct MergedExport {
+  pub name: String,
+  /// The indexes of the configurations that have the export.
+  pub found_in: Vec<usize>,
+  /// Whether the export only exists in some of the configurations.
+  pub partial: bool,
+}
+
+/// A reexport that is found in some of the analyzed configurations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct MergedReexport {
+  pub specifier: String,
+  pub called: bool,
+  pub member: Option<String>,
+  /// The indexes of the configurations that have the reexport.
+  pub found_in: Vec<usize>,
+  /// Whether the reexport only exists in some of the configurations.
+  pub partial: bool,
+}
+
+/// A named reexport that is found in some of the analyzed configurations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct MergedNamedReexport {
+  pub name: String,
+  pub specifier: String,
+  pub member: Option<String>,
+  /// The indexes of the configurations that have the named reexport.
+  pub found_in: Vec<usize>,
+  /// Whether the named reexport only exists in some of the configurations.
+  pub partial: bool,
+}
+
+/// The results of analyzing a module with several configurations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergedAnalyzeResult {
+  /// The result for each configuration, in the order of the given options.
+  pub results: Vec<AnalyzeResult>,
+  /// The union of the named exports of all configurations.
+  pub exports: Vec<MergedExport>,
+  /// The union of the reexports of all configurations.
+  pub reexports: Vec<MergedReexport>,
+  /// The union of the named reexports of all configurations.
+  pub named_reexports: Vec<MergedNamedReexport>,
+}
+
+impl MergedAnalyzeResult {
+  pub(crate) fn new(results: Vec<AnalyzeResult>) -> Self {
+    let mut exports: Vec<MergedExport> = vec![];
+    let mut reexports: Vec<MergedReexport> = vec![];
+    let mut named_reexports: Vec<MergedNamedReexport> = vec![];
+    for (index, result) in results.iter().enumerate() {
+      for export in &result.exports {
+        match exports.iter_mut().find(|e| e.name == export.name) {
+          Some(merged) => merged.found_in.push(index),
+          None => exports.push(MergedExport {
+            name: export.name.clone(),
+            found_in: vec![index],
+            partial: false,
+          }),
+        }
+      }
+      for reexport in &result.reexports {
+        match reexports
+          .iter_mut()
+          .find(|r| r.specifier == reexport.specifier && r.called == reexport.called && r.member == reexport.member)
+        {
+          Some(merged) => merged.found_in.push(index),
+          None => reexports.push(MergedReexport {
+            specifier: reexport.specifier.clone(),
+            called: reexport.called,
+            member: reexport.member.clone(),
+            found_in: vec![index],
+            partial: false,
+          }),
+        }
+      }
+      for named in &result.named_reexports {
+        match named_reexports
+          .iter_mut()
+          .find(|r| r.name == named.name && r.specifier == named.specifier && r.member == named.member)
+        {
+          Some(merged) => merged.found_in.push(index),
+          None => named_reexports.push(MergedNamedReexport {
+            name: named.name.clone(),
+            specifier: named.specifier.clone(),
+            member: named.member.clone(),
+            found_in: vec![index],
+            partial: false,
+          }),
+        }
+      }
+    }
+    for export in exports.iter_mut() {
+      export.partial = export.found_in.len() < results.len();
+    }
+    for reexport in reexports.iter_mut() {
+      reexport.partial = reexport.found_in.len() < results.len();
+    }
+    for named in named_reexports.iter_mut() {
+      named.partial = named.found_in.len() < results.len();
+    }
+    MergedAnalyzeResult {
+      results,
+      exports,
+      reexports,
+      named_reexports,
+    }
+  }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+/// A map of names whose insertions are undone when the current scope is left,
+/// so nested bodies can shadow names without cloning the whole map.
+pub struct ScopedMap<V> {
+  map: HashMap<String, V>,
+  /// the replaced values, restored in reverse order when leaving a scope.
+  undo: Vec<(String, Option<V>)>,
+  /// the length of `undo` when each scope was entered.
+  marks: Vec<usize>,
+}
+
+impl<V> Default for ScopedMap<V> {
+  fn default() -> Self {
+    ScopedMap {
+      map: HashMap::new(),
+      undo: vec![],
+      marks: vec![],
+    }
+  }
+}
+
+impl<V> ScopedMap<V> {
+  pub fn get(&self, name: &str) -> Option<&V> {
+    self.map.get(name)
+  }
+
+  pub fn contains_key(&self, name: &str) -> bool {
+    self.map.contains_key(name)
+  }
+
+  pub fn insert(&mut self, name: String, value: V) {
+    if self.marks.is_empty() {
+      self.map.insert(name, value);
+    } else {
+      let replaced = self.map.insert(name.clone(), value);
+      self.undo.push((name, replaced));
+    }
+  }
+
+  pub fn enter(&mut self) {
+    self.marks.push(self.undo.len());
+  }
+
+  pub fn leave(&mut self) {
+    let mark = self.marks.pop().expect("no scope to leave");
+    while self.undo.len() > mark {
+      let (name, replaced) = self.undo.pop().unwrap();
+      match replaced {
+        Some(value) => self.map.insert(name, value),
+        None => self.map.remove(&name),
+      };
+    }
+  }
+}
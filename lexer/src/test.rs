@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
   use crate::*;
+  use std::collections::HashMap;
 
   #[test]
   fn parse_cjs_exports_case_1() {
@@ -16,8 +17,8 @@ mod tests {
       Object.defineProperty(module.exports, '__esModule', { value: 1 });
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "a,b,c,d,e,g,__esModule")
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "a,b,c,d,e,g,__esModule")
   }
 
   #[test]
@@ -29,9 +30,9 @@ mod tests {
       Object.defineProperty(module, 'exports', { value: { alas, foo: 'bar', ...obj, ...require('a'), ...require('b') } })
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "alas,foo,bar");
-    assert_eq!(reexports.join(","), "a,b");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "alas,foo,bar");
+    assert_eq!(result.reexport_specifiers().join(","), "a,b");
   }
 
   #[test]
@@ -43,9 +44,9 @@ mod tests {
       Object.assign(module.exports, { alas, foo: 'bar', ...obj }, { ...require('a') }, require('b'))
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "alas,foo,bar,meta");
-    assert_eq!(reexports.join(","), "a,b");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "alas,foo,bar,meta");
+    assert_eq!(result.reexport_specifiers().join(","), "a,b");
   }
 
   #[test]
@@ -55,9 +56,9 @@ mod tests {
       Object.assign(module, { exports: { nope: true } })
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "nope");
-    assert_eq!(reexports.join(","), "");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "nope");
+    assert_eq!(result.reexport_specifiers().join(","), "");
   }
 
   #[test]
@@ -67,8 +68,8 @@ mod tests {
       module.exports.bar = 123
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -82,9 +83,9 @@ mod tests {
       module.exports = { alas,  ...obj, ...require('a'), ...require('b') }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "alas,boom,coco");
-    assert_eq!(reexports.join(","), "a,b");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "alas,boom,coco");
+    assert_eq!(result.reexport_specifiers().join(","), "a,b");
   }
 
   #[test]
@@ -94,8 +95,8 @@ mod tests {
       module['exports']['bar'] = 123
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -105,8 +106,8 @@ mod tests {
       module.exports.foo = 'bar';
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -115,8 +116,8 @@ mod tests {
       module.exports = require("lib")
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (_, reexports) = lexer.analyze("development", false);
-    assert_eq!(reexports.join(","), "lib");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
   }
 
   #[test]
@@ -126,8 +127,8 @@ mod tests {
       module.exports = lib
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (_, reexports) = lexer.analyze("development", false);
-    assert_eq!(reexports.join(","), "lib");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
   }
 
 
@@ -140,8 +141,8 @@ mod tests {
       module.exports = Module
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -152,8 +153,8 @@ mod tests {
       module.exports = Module
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -164,8 +165,8 @@ mod tests {
       module.exports = Module
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -176,9 +177,9 @@ mod tests {
       module.exports = lib
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
-    assert_eq!(reexports.join(","), "lib");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
   }
 
   #[test]
@@ -188,8 +189,8 @@ mod tests {
       e.foo = 'bar'
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -199,8 +200,8 @@ mod tests {
       mod.foo = 'bar'
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -215,8 +216,8 @@ mod tests {
       module.exports = Module
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,greeting");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,greeting");
   }
 
   #[test]
@@ -227,8 +228,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -239,8 +240,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -251,8 +252,8 @@ mod tests {
       }())
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -263,8 +264,8 @@ mod tests {
       }()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -275,8 +276,8 @@ mod tests {
       }()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -291,8 +292,8 @@ mod tests {
       }).call(this)
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -307,8 +308,8 @@ mod tests {
       }).call(this)
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -320,8 +321,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -333,8 +334,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -347,8 +348,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -360,8 +361,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -373,8 +374,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -386,8 +387,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -399,8 +400,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -411,8 +412,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "");
   }
 
   #[test]
@@ -423,8 +424,8 @@ mod tests {
       }
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -435,8 +436,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", false);
-    assert_eq!(exports.join(","), "");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "");
   }
 
   #[test]
@@ -447,8 +448,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -464,8 +465,8 @@ mod tests {
       })()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -475,8 +476,8 @@ mod tests {
       module.exports = fn()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -486,8 +487,8 @@ mod tests {
       module.exports = fn()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -501,8 +502,8 @@ mod tests {
       module.exports = fn()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", false);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -511,8 +512,57 @@ mod tests {
       module.exports = require("lib")()
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (_, reexports) = lexer.analyze("development", false);
-    assert_eq!(reexports.join(","), "lib()");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
+    assert!(result.reexports[0].called);
+  }
+
+  #[test]
+  fn parse_cjs_exports_case_17_1() {
+    let source = r#"
+      var lib = require("lib");
+      lib.foo = "bar";
+      module.exports = lib.default
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "");
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
+    assert_eq!(result.reexports[0].member.as_deref(), Some("default"));
+    assert!(!result.reexports[0].called);
+  }
+
+  #[test]
+  fn parse_cjs_exports_case_17_2() {
+    let source = r#"
+      var lib = require("./lib()");
+      lib.foo = "bar";
+      module.exports = lib
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./lib()");
+    assert_eq!(result.reexports[0].extends.join(","), "foo");
+    assert!(!result.reexports[0].called);
+  }
+
+  #[test]
+  fn parse_cjs_exports_case_17_3() {
+    let source = r#"
+      const name = "default";
+      if (process.env.NODE_ENV === "development") {
+        module.exports = require("./lib")[name];
+      } else {
+        module.exports = require("./lib")[process.env.ENTRY];
+      }
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.reexport_specifiers().join(","), "./lib");
+    assert_eq!(result.reexports[0].member.as_deref(), Some("default"));
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.reexport_specifiers().join(","), "");
   }
 
   #[test]
@@ -525,8 +575,8 @@ mod tests {
       };
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -540,8 +590,8 @@ mod tests {
       module.exports = fn;
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -555,8 +605,8 @@ mod tests {
       module.exports = fn;
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("development", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("development", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -574,8 +624,8 @@ mod tests {
       module.exports = fn;
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -593,8 +643,8 @@ mod tests {
       module.exports = fn;
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -604,8 +654,8 @@ mod tests {
       exports.bar = 123
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -616,8 +666,8 @@ mod tests {
       exports.bar = 123
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -628,8 +678,8 @@ mod tests {
       exports.bar = 123
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
   }
 
   #[test]
@@ -639,8 +689,8 @@ mod tests {
       (0, tslib_1.__exportStar)(require("./crossPlatformSha256"), exports);
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (_, reexorts) = lexer.analyze("production", true);
-    assert_eq!(reexorts.join(","), "./crossPlatformSha256");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.reexport_specifiers().join(","), "./crossPlatformSha256");
   }
 
   #[test]
@@ -651,9 +701,9 @@ mod tests {
       __exportStar(require("./bar"), exports);
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexorts) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo");
-    assert_eq!(reexorts.join(","), "./bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./bar");
   }
 
   #[test]
@@ -665,8 +715,8 @@ mod tests {
       exports.greeting = "hello";
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar,greeting");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar,greeting");
   }
 
   #[test]
@@ -677,8 +727,8 @@ mod tests {
       exports.greeting = "hello";
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar,greeting");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar,greeting");
   }
 
   #[test]
@@ -688,8 +738,8 @@ mod tests {
       exports.greeting = "hello";
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,greeting");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,greeting");
   }
 
   #[test]
@@ -699,8 +749,8 @@ mod tests {
       exports.greeting = "hello";
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,greeting");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,greeting");
   }
 
   #[test]
@@ -709,8 +759,8 @@ mod tests {
       var title = exports.name = exports.title = exports.short = "untitled";
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "name,title,short");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "name,title,short");
   }
 
   #[test]
@@ -726,8 +776,8 @@ mod tests {
       }))
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,__esModule");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,__esModule");
   }
 
   #[test]
@@ -743,8 +793,8 @@ mod tests {
       })))
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,__esModule");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,__esModule");
   }
 
   #[test]
@@ -814,8 +864,8 @@ mod tests {
       }));
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,named,default");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,named,default");
   }
 
   #[test]
@@ -869,8 +919,8 @@ mod tests {
       ));
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,default,named1,named2");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,default,named1,named2");
   }
 
   #[test]
@@ -941,8 +991,8 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,default,named1,named2");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,default,named1,named2");
   }
 
   #[test]
@@ -981,8 +1031,8 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,named2,named1,default");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,named2,named1,default");
   }
 
   #[test]
@@ -1022,8 +1072,8 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,named2,named1,default");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,named2,named1,default");
   }
 
   #[test]
@@ -1063,8 +1113,8 @@ mod tests {
     });
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "default,named1,named2,__esModule");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "default,named1,named2,__esModule");
   }
 
   #[test]
@@ -1174,8 +1224,8 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,default,named1");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,default,named1");
   }
 
   #[test]
@@ -1299,9 +1349,9 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
+    let result = lexer.analyze("production", true);
     assert_eq!(
-      exports.join(","),
+      result.export_names().join(","),
       "__esModule,Context,TTag,_,addLocale,c,gettext,jt,msgid,ngettext,setDedent,setDefaultLang,t,useLocale,useLocales"
     );
   }
@@ -1385,8 +1435,8 @@ mod tests {
       })());
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,app");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,app");
   }
 
   #[test]
@@ -1454,8 +1504,8 @@ mod tests {
     });
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "HighchartsReact,default");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "HighchartsReact,default");
   }
 
   #[test]
@@ -1465,8 +1515,8 @@ mod tests {
       url.foo = 'bar';
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo");
   }
 
   #[test]
@@ -1475,8 +1525,8 @@ mod tests {
       exports.i18n = exports.use = exports.t = undefined;
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "i18n,use,t");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "i18n,use,t");
   }
 
   #[test]
@@ -1487,9 +1537,9 @@ mod tests {
       __export(require("./lib"));
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, reexports) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "__esModule,foo");
-    assert_eq!(reexports.join(","), "./lib");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "__esModule,foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./lib");
   }
 
   #[test]
@@ -1501,7 +1551,1598 @@ mod tests {
     });
     "#;
     let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
-    let (exports, _) = lexer.analyze("production", true);
-    assert_eq!(exports.join(","), "foo,bar");
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_spans() {
+    let source = r#"exports.foo = 'bar';
+if (true) {
+  module.exports.bar = 123;
+}
+Object.assign(module.exports, require('lib'));
+"#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+    assert_eq!(
+      result.exports[0].span,
+      SourceSpan {
+        start: 0,
+        end: 20,
+        line: 1,
+        column: 0
+      }
+    );
+    assert_eq!(
+      result.exports[1].span,
+      SourceSpan {
+        start: 35,
+        end: 60,
+        line: 3,
+        column: 2
+      }
+    );
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
+    assert_eq!(result.reexports[0].span.line, 5);
+    assert_eq!(result.reexports[0].span.column, 0);
+  }
+
+  #[test]
+  fn parse_cjs_exports_provenance() {
+    let source = r#"
+      0 && (module.exports = { e, f });
+      exports.a = 1;
+      Object.defineProperty(exports, "b", { value: 1 });
+      Object.assign(exports, { c: 1 }, require("./assign"));
+      var d = exports.d || (exports.d = {});
+      __exportStar(require("./star"), exports);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "e,f,a,b,c,d");
+    let provenances = result.exports.iter().map(|e| e.provenance).collect::<Vec<_>>();
+    assert_eq!(
+      provenances,
+      vec![
+        Provenance::EsbuildAnnotation,
+        Provenance::EsbuildAnnotation,
+        Provenance::Assignment,
+        Provenance::DefineProperty,
+        Provenance::ObjectAssign,
+        Provenance::LogicalAssignment,
+      ]
+    );
+    assert_eq!(result.reexport_specifiers().join(","), "./assign,./star");
+    assert_eq!(result.reexports[0].provenance, Provenance::ObjectAssign);
+    assert_eq!(result.reexports[1].provenance, Provenance::TslibExportStar);
+
+    let source = r#"
+      function Foo() {}
+      Foo.bar = 1;
+      module.exports = Foo;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "bar");
+    assert_eq!(result.exports[0].provenance, Provenance::FunctionProperty);
+  }
+
+  #[test]
+  fn parse_cjs_default_kind() {
+    let cases = [
+      ("exports.foo = 1", DefaultKind::Object, false, false),
+      ("module.exports = { foo: 1 }", DefaultKind::Object, false, false),
+      ("module.exports = function () {}", DefaultKind::Function, true, true),
+      (
+        "module.exports = async function () {}",
+        DefaultKind::Function,
+        true,
+        false,
+      ),
+      ("module.exports = () => {}", DefaultKind::Function, true, false),
+      ("class Foo {}; module.exports = Foo", DefaultKind::Class, false, true),
+      ("module.exports = 'foo'", DefaultKind::Primitive, false, false),
+      (
+        "const foo = 1; module.exports = foo",
+        DefaultKind::Primitive,
+        false,
+        false,
+      ),
+      ("module.exports = void 0", DefaultKind::Primitive, false, false),
+      ("module.exports = require('lib')", DefaultKind::Reexport, false, false),
+      ("module.exports = foo()", DefaultKind::Unknown, false, false),
+    ];
+    for (source, default_kind, callable, constructible) in cases {
+      let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+      let result = lexer.analyze("production", false);
+      assert_eq!(result.default_kind, default_kind, "{}", source);
+      assert_eq!(result.callable, callable, "{}", source);
+      assert_eq!(result.constructible, constructible, "{}", source);
+      assert!(!result.es_module, "{}", source);
+    }
+
+    let source = r#"
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.default = function () {};
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.default_kind, DefaultKind::Object);
+    assert!(result.es_module);
+
+    let source = r#"
+      module.exports = function () {
+        return { foo: "bar" };
+      };
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    // the result describes the value returned by `module.exports()` in call mode
+    let result = lexer.analyze("production", true);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(result.default_kind, DefaultKind::Object);
+    assert!(!result.callable);
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.default_kind, DefaultKind::Function);
+    assert!(result.callable);
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_defines() {
+    let source = r#"
+      if (process.env.BROWSER) {
+        exports.browser = true;
+      } else {
+        exports.server = true;
+      }
+      if (!__DEV__) {
+        exports.prod = true;
+      }
+      if (typeof window !== "undefined") {
+        exports.window = true;
+      }
+      const { __NEXT_RUNTIME: runtime } = process.env;
+      if (runtime === "edge") {
+        exports.edge = true;
+      }
+      (function () {
+        var __DEV__ = true;
+        if (__DEV__) {
+          exports.shadowed = true;
+        }
+      })();
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "browser,prod,window,shadowed");
+
+    let options = AnalyzeOptions {
+      defines: HashMap::from([
+        ("process.env.BROWSER".to_owned(), "true".to_owned()),
+        ("__DEV__".to_owned(), "false".to_owned()),
+        ("typeof window".to_owned(), "'undefined'".to_owned()),
+        ("process.env.__NEXT_RUNTIME".to_owned(), "\"edge\"".to_owned()),
+      ]),
+      ..AnalyzeOptions::default()
+    };
+    let result = lexer.analyze_with_options(&options);
+    assert_eq!(result.export_names().join(","), "browser,prod,edge,shadowed");
+    assert!(result.warnings.is_empty());
+
+    // a define that is not a literal is ignored with a warning
+    let options = AnalyzeOptions {
+      defines: HashMap::from([
+        ("process.env.BROWSER".to_owned(), "window".to_owned()),
+        ("__DEV__".to_owned(), "false".to_owned()),
+      ]),
+      ..AnalyzeOptions::default()
+    };
+    let result = lexer.analyze_with_options(&options);
+    assert_eq!(result.export_names().join(","), "browser,prod,window,shadowed");
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].kind, WarningKind::InvalidDefine);
+    assert_eq!(
+      result.warnings[0].message,
+      "could not parse the value of the define process.env.BROWSER: window"
+    );
+
+    // the `process` module is the same object as the global
+    for specifier in ["process", "node:process"] {
+      let source = format!(
+        r#"
+          var process = require("{}");
+          if (process.env.NODE_ENV === "production") {{
+            module.exports = require("./prod");
+          }} else {{
+            module.exports = require("./dev");
+          }}
+        "#,
+        specifier
+      );
+      let lexer = CommonJSModuleLexer::init("index.cjs", &source).expect("could not parse the module");
+      let result = lexer.analyze("production", false);
+      assert_eq!(result.reexport_specifiers().join(","), "./prod", "{}", specifier);
+      let result = lexer.analyze("development", false);
+      assert_eq!(result.reexport_specifiers().join(","), "./dev", "{}", specifier);
+    }
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_platform() {
+    let source = r#"
+      var isBrowser = typeof window !== "undefined" && typeof document !== "undefined";
+      if (isBrowser) {
+        module.exports = require("./browser.js");
+      } else if (typeof Deno !== "undefined") {
+        module.exports = require("./deno.js");
+      } else if (typeof self === "object" && typeof importScripts === "function") {
+        module.exports = require("./worker.js");
+      } else if (typeof process !== "undefined") {
+        module.exports = require("./node.js");
+      }
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    for (platform, specifier) in [
+      (Platform::Browser, "./browser.js"),
+      (Platform::Deno, "./deno.js"),
+      (Platform::Worker, "./worker.js"),
+      (Platform::Node, "./node.js"),
+    ] {
+      let result = lexer.analyze_with_options(&AnalyzeOptions {
+        platform: Some(platform),
+        ..AnalyzeOptions::default()
+      });
+      assert_eq!(result.reexport_specifiers().join(","), specifier);
+    }
+
+    let source = r#"
+      if (typeof exports === "object" && typeof module !== "undefined") {
+        exports.foo = "bar";
+      } else if (typeof define === "function" && define.amd) {
+        define(["exports"], function (exports) { exports.foo = "bar"; });
+      } else {
+        window.foo = "bar";
+      }
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze_with_options(&AnalyzeOptions {
+      platform: Some(Platform::Browser),
+      ..AnalyzeOptions::default()
+    });
+    assert_eq!(result.export_names().join(","), "foo");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_multiple_envs() {
+    let source = r#"
+      exports.common = true;
+      if (process.env.NODE_ENV === "development") {
+        exports.debug = true;
+        Object.assign(exports, require("./dev"));
+      } else {
+        exports.prod = true;
+        exports.parse = require("./parser").parse;
+      }
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let development = AnalyzeOptions {
+      node_env: "development".to_owned(),
+      ..AnalyzeOptions::default()
+    };
+    let production = AnalyzeOptions::default();
+    let merged = lexer.analyze_all(&[development.clone(), production, development]);
+    assert_eq!(merged.results.len(), 3);
+    assert_eq!(merged.results[0].export_names().join(","), "common,debug");
+    assert_eq!(merged.results[1].export_names().join(","), "common,prod,parse");
+    assert_eq!(merged.results[0], merged.results[2]);
+    let exports = merged
+      .exports
+      .iter()
+      .map(|e| format!("{}:{:?}:{}", e.name, e.found_in, e.partial))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      exports.join(","),
+      "common:[0, 1, 2]:false,debug:[0, 2]:true,prod:[1]:true,parse:[1]:true"
+    );
+    assert_eq!(merged.reexports.len(), 1);
+    assert_eq!(merged.reexports[0].specifier, "./dev");
+    assert_eq!(merged.reexports[0].found_in, vec![0, 2]);
+    assert!(merged.reexports[0].partial);
+    assert_eq!(merged.named_reexports.len(), 1);
+    assert_eq!(merged.named_reexports[0].name, "parse");
+    assert_eq!(merged.named_reexports[0].member.as_deref(), Some("parse"));
+    assert_eq!(merged.named_reexports[0].found_in, vec![1]);
+    assert!(merged.named_reexports[0].partial);
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_scoped_idents() {
+    let source = r#"
+      var lib = { a: 1 };
+      (function () {
+        var lib = { b: 2 };
+        var alias = exports;
+        alias.inner = true;
+        lib.c = 3;
+      })();
+      alias.outer = true;
+      lib.d = 4;
+      module.exports = lib;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "a,d");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_script_goal() {
+    let source = r#"
+      <!-- legacy html comment
+      var await = 1;
+      var mode = 010;
+      with (Math) {
+        exports.pi = PI;
+      }
+      exports.foo = "bar";
+      if (typeof window !== "undefined") {
+        return;
+      }
+      exports.node = true;
+      return;
+      exports.unreachable = true;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "pi,foo,node");
+
+    // the condition of the early return is unknown
+    let source = r#"
+      if (!globalThis.fetch) return;
+      var lib = require("./lib");
+      module.exports = lib;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.reexport_specifiers().join(","), "./lib");
+    assert_eq!(result.default_kind, DefaultKind::Reexport);
+    assert!(result.warnings.is_empty());
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_module_syntax() {
+    let source = r#"
+      import { foo } from "./foo";
+      exports.foo = foo;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_syntax_errors() {
+    let source = r#"
+      exports.a = "a";
+      exports.b = ;
+      function c() {
+        exports.c = "c"
+      }
+      exports.d = "d" +;
+      exports.e = "e";
+    "#;
+    assert!(CommonJSModuleLexer::init("index.cjs", source).is_err());
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", source);
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "a,e");
+    assert_eq!(diagnostics.diagnostics().len(), 2);
+    assert!(diagnostics.to_string().contains("at index.cjs:3:18,"));
+    assert!(diagnostics.to_string().ends_with("at index.cjs:7:23"));
+    assert_eq!(result.exports[1].span.line, 8);
+
+    // an unterminated call at the end of the source drops the call, not the whole module
+    let source = format!(
+      "exports.a = 'a';\ndefine(function () {{\n{}  if (x) {{\n",
+      "  exports.b = 'b';\n".repeat(32)
+    );
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", &source);
+    assert_eq!(lexer.analyze("production", false).export_names().join(","), "a");
+    assert_eq!(diagnostics.diagnostics().len(), 1);
+
+    // a long run of broken lines
+    let source = format!("exports.a = 'a';\n{}exports.z = 'z';\n", "exports.x = ;\n".repeat(20));
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", &source);
+    assert_eq!(lexer.analyze("production", false).export_names().join(","), "a,z");
+    assert_eq!(diagnostics.diagnostics().len(), 20);
+
+    // a large module with too many errors keeps the exports before the last one
+    let mut source = String::new();
+    for i in 0..20 {
+      source.push_str(&format!("exports.a{} = {};\nexports.b = ;\n", i, i));
+    }
+    source.push_str(&format!("/* {} */\nexports.z = 'z';\n", "-".repeat(1024 * 1024)));
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", &source);
+    let names = (0..16).map(|i| format!("a{}", i)).collect::<Vec<_>>();
+    assert_eq!(
+      lexer.analyze("production", false).export_names().join(","),
+      names.join(",")
+    );
+    assert_eq!(diagnostics.diagnostics().len(), 16);
+
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", "exports.foo = 'bar';");
+    assert!(diagnostics.is_empty());
+    assert_eq!(lexer.analyze("production", false).export_names().join(","), "foo");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_structured_diagnostics() {
+    let source = "exports.a = 1;\nexports.b = ;\n";
+    let diagnostics = match CommonJSModuleLexer::init("index.cjs", source) {
+      Ok(_) => panic!("should not parse the module"),
+      Err(diagnostics) => diagnostics.into_diagnostics(),
+    };
+    assert_eq!(
+      diagnostics,
+      vec![Diagnostic {
+        severity: Severity::Error,
+        message: "Expression expected".to_owned(),
+        code: Some("TS1109".to_owned()),
+        file: "index.cjs".to_owned(),
+        line: 2,
+        column: 12,
+        span: Some(SourceSpan {
+          start: 27,
+          end: 28,
+          line: 2,
+          column: 12,
+        }),
+      }]
+    );
+    assert_eq!(diagnostics[0].to_string(), "Expression expected at index.cjs:2:12");
+
+    // the code is the kind of the syntax error, whatever its message is
+    let (_, diagnostics) = CommonJSModuleLexer::init_recoverable("index.cjs", "exports.a = ;\nexports.b = 'b;\n");
+    let diagnostics = diagnostics.into_diagnostics();
+    let codes = diagnostics
+      .iter()
+      .map(|d| d.code.as_deref().unwrap_or_default())
+      .collect::<Vec<_>>();
+    assert_eq!(codes, vec!["TS1109", "UnterminatedStrLit"]);
+    assert_eq!((diagnostics[1].line, diagnostics[1].column), (2, 12));
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_warnings() {
+    let source = r#"
+      const key = "foo";
+      exports[key] = 1;
+      for (const name of names) {
+        exports[name] = true;
+        Object.defineProperty(exports, name, { get: () => 1 });
+      }
+      if (process.env.NODE_ENV === "development") {
+        module.exports = require("./dev");
+      } else {
+        module.exports = createExports();
+      }
+      0 && (exports[name] = 1);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("development", false);
+    assert_eq!(result.export_names().join(","), "");
+    assert_eq!(result.reexport_specifiers().join(","), "./dev");
+    assert_eq!(
+      result.warnings.iter().map(|w| w.kind).collect::<Vec<_>>(),
+      vec![WarningKind::DynamicAssignment, WarningKind::DynamicDefineProperty]
+    );
+    let result = lexer.analyze("production", false);
+    let warnings = result
+      .warnings
+      .iter()
+      .map(|w| (w.kind, w.message.as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      warnings,
+      vec![
+        (
+          WarningKind::DynamicAssignment,
+          "could not resolve the dynamic export assignment at 5:8"
+        ),
+        (
+          WarningKind::DynamicDefineProperty,
+          "could not resolve the dynamic export property definition at 6:8"
+        ),
+        (
+          WarningKind::UnknownModuleExports,
+          "could not resolve the value assigned to module.exports at 11:25"
+        )
+      ]
+    );
+
+    let source = r#"
+      const key = "foo";
+      exports[key] = 1;
+      exports[getName()] = 2;
+      Object.defineProperty(exports, Symbol.for("bar"), { value: 3 });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(
+      result.warnings.iter().map(|w| w.message.as_str()).collect::<Vec<_>>(),
+      vec![
+        "could not resolve the dynamic export assignment at 4:6",
+        "could not resolve the dynamic export property definition at 5:6"
+      ]
+    );
+
+    let source = r#"
+      for (var i = 0; i < names.length; i++) exports[names[i]] = 1;
+      names.forEach(n => exports[n] = 1);
+      for (const k of getNames()) module.exports[k] = true;
+      ["foo", "bar"].forEach(n => exports[n] = 1);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+    assert_eq!(
+      result.warnings.iter().map(|w| w.message.as_str()).collect::<Vec<_>>(),
+      vec![
+        "could not resolve the dynamic export assignment at 2:45",
+        "could not resolve the dynamic export assignment at 3:25",
+        "could not resolve the dynamic export assignment at 4:34"
+      ]
+    );
+  }
+
+  #[test]
+  fn parse_module_format() {
+    let source = r#"
+      import { createRequire } from "module";
+      const require = createRequire(import.meta.url);
+      export const a = 1, { b, c: [d] } = require("./lib");
+      export function e() {}
+      export default class {}
+      export { a as f, b as "g" };
+      export * as ns from "./ns";
+      export * from "./star";
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::Esm);
+    assert_eq!(result.esm_exports.join(","), "a,b,d,e,default,f,g,ns");
+    assert_eq!(result.esm_reexports.join(","), "./star");
+    assert_eq!(result.export_names().join(","), "");
+
+    let source = r#"
+      import foo from "./foo";
+      exports.foo = foo;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::Mixed);
+    assert_eq!(result.export_names().join(","), "foo");
+
+    let lexer = CommonJSModuleLexer::init("index.js", "exports.foo = 1;").expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::Cjs);
+    assert!(result.esm_exports.is_empty());
+
+    let lexer = CommonJSModuleLexer::init("index.js", "console.log(1);").expect("could not parse the module");
+    assert_eq!(lexer.analyze("production", false).format, ModuleFormat::Unknown);
+
+    // the commonjs globals are shadowed by the params of the module wrappers
+    let source = r#"
+      (function (modules) {
+        modules[0].call(null, {}, {}, function (id) { return modules[id]; });
+      })([
+        function (module, exports, __webpack_require__) {
+          exports.foo = __webpack_require__(1);
+        },
+        function (require, module, exports) {
+          module.exports = require(0);
+        },
+      ]);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    assert_eq!(lexer.analyze("production", false).format, ModuleFormat::Unknown);
+  }
+
+  #[test]
+  fn parse_cjs_requires() {
+    let source = r#"
+      const a = require("a");
+      (function () {
+        require(`b`);
+      })();
+      if (process.env.NODE_ENV === "production") {
+        module.exports = require("./prod");
+      }
+      try {
+        require("optional");
+      } catch (e) {
+        require("fallback");
+      }
+      function lazy(lang) {
+        return require("./locale/" + lang);
+      }
+      const c = a.c || require("c");
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    let requires = result
+      .requires
+      .iter()
+      .map(|r| format!("{}:{}:{}:{}", r.specifier, r.dynamic, r.conditional, r.in_try))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      requires,
+      vec![
+        "a:false:false:false",
+        "b:false:false:false",
+        "./prod:false:true:false",
+        "optional:false:false:true",
+        "fallback:false:true:false",
+        "\"./locale/\" + lang:true:true:false",
+        "c:false:true:false",
+      ]
+    );
+    assert_eq!(result.requires[0].span.line, 2);
+    assert_eq!(result.requires[0].span.column, 16);
+
+    // the `require` of a module wrapper is not the commonjs global
+    let source = r#"
+      (function (require) {
+        require(0);
+      })(bundleRequire);
+      var modules = [
+        function (require, module, exports) {
+          module.exports = require("./bundled");
+        },
+        (module, exports, require) => require("./bundled"),
+        function () {
+          function require(id) {}
+          require("./local");
+        },
+      ];
+      const lib = require("lib");
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(
+      result.requires.iter().map(|r| r.specifier.as_str()).collect::<Vec<_>>(),
+      vec!["lib"]
+    );
+  }
+
+  #[test]
+  fn parse_cjs_named_reexports_from_getters() {
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.bar = exports.foo = void 0;
+      var foo_1 = require("./foo");
+      Object.defineProperty(exports, "foo", { enumerable: true, get: function () { return foo_1.foo; } });
+      Object.defineProperty(exports, "baz", { enumerable: true, get: function () { return require("./baz").qux; } });
+      Object.defineProperty(exports, "ns", { enumerable: true, get: () => foo_1 });
+      Object.defineProperty(exports, "local", { enumerable: true, get() { return local; } });
+      __createBinding(exports, require("./a"), "a");
+      (0, tslib_1.__createBinding)(exports, foo_1, "default", "b");
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,bar,foo,baz,ns,local,a,b");
+    assert_eq!(
+      result.named_reexport_triples(),
+      vec![
+        "foo:./foo:foo",
+        "baz:./baz:qux",
+        "ns:./foo:*",
+        "a:./a:a",
+        "b:./foo:default"
+      ]
+    );
+    assert_eq!(result.named_reexports[3].provenance, Provenance::CreateBinding);
+    assert!(result.reexports.is_empty());
+  }
+
+  #[test]
+  fn parse_cjs_named_reexports_from_assignments() {
+    let source = r#"
+      var utils = require("./utils");
+      exports.parse = require("./parser").parse;
+      module.exports.stringify = exports.serialize = utils.stringify;
+      exports.utils = utils;
+      exports.create = require("./factory")();
+      exports.version = "1.0.0";
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(
+      result.export_names().join(","),
+      "parse,stringify,serialize,utils,create,version"
+    );
+    assert_eq!(
+      result.named_reexport_triples(),
+      vec![
+        "parse:./parser:parse",
+        "stringify:./utils:stringify",
+        "serialize:./utils:stringify",
+        "utils:./utils:*"
+      ]
+    );
+
+    let source = r#"
+      const lib = require("./lib");
+      module.exports = { foo: require("./foo").default, lib, bar: 1 };
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,lib,bar");
+    assert_eq!(
+      result.named_reexport_triples(),
+      vec!["foo:./foo:default", "lib:./lib:*"]
+    );
+
+    // the last assignment wins
+    let source = r#"
+      exports.foo = require("./a").foo;
+      exports.foo = 1;
+      exports.bar = 1;
+      exports.bar = require("./b").bar;
+      exports.baz = require("./a").baz;
+      exports.baz = require("./b").baz;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar,baz");
+    assert_eq!(result.named_reexport_triples().join(","), "bar:./b:bar,baz:./b:baz");
+
+    let source = r#"
+      module.exports = require("./lib").default;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.reexports[0].specifier, "./lib");
+    assert_eq!(result.reexports[0].member.as_deref(), Some("default"));
+
+    // a computed member is only a named reexport if the key is a constant string
+    let source = r#"
+      var lib = require("./lib");
+      var key = "bar";
+      exports.foo = lib[k];
+      exports.bar = lib[key];
+      exports.baz = lib["baz"];
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar,baz");
+    assert_eq!(result.named_reexport_triples().join(","), "bar:./lib:bar,baz:./lib:baz");
+  }
+
+  #[test]
+  fn parse_cjs_babel_export_star() {
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", { value: true });
+      var _exportNames = { foo: true };
+      exports.foo = void 0;
+      var _bar = require("./bar");
+      Object.keys(_bar).forEach(function (key) {
+        if (key === "default" || key === "__esModule") return;
+        if (Object.prototype.hasOwnProperty.call(_exportNames, key)) return;
+        if (key in exports && exports[key] === _bar[key]) return;
+        Object.defineProperty(exports, key, {
+          enumerable: true,
+          get: function () {
+            return _bar[key];
+          }
+        });
+      });
+      var _baz = require("./baz");
+      Object.keys(_baz).forEach((k) => exports[k] = _baz[k]);
+      Object.keys(unknown).forEach(function (key) {
+        exports[key] = unknown[key];
+      });
+      Object.keys(require("./qux")).forEach(function (key) {
+        console.log(key);
+      });
+      var _quux = _interopRequireWildcard(require("./quux"));
+      Object.keys(_quux).forEach(function (key) {
+        if (key === "default" || key === "__esModule") return;
+        exports[key] = _quux[key];
+      });
+      var _only = require("./only");
+      Object.keys(_only).forEach(function (key) {
+        if (key === "x") exports[key] = _only[key];
+      });
+      const foo = exports.foo = 1;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./bar,./baz,./quux");
+    assert_eq!(result.reexports[0].provenance, Provenance::BabelExportStar);
+  }
+
+  #[test]
+  fn parse_cjs_exports_esbuild_to_commonjs() {
+    let source = r#"
+      var __defProp = Object.defineProperty;
+      var __getOwnPropNames = Object.getOwnPropertyNames;
+      var __hasOwnProp = Object.prototype.hasOwnProperty;
+      var __export = (target, all) => {
+        for (var name in all)
+          __defProp(target, name, { get: all[name], enumerable: true });
+      };
+      var __copyProps = (to, from, except) => {
+        for (let key of __getOwnPropNames(from))
+          if (!__hasOwnProp.call(to, key) && key !== except)
+            __defProp(to, key, { get: () => from[key] });
+        return to;
+      };
+      var __reExport = (target, mod, secondTarget) => (__copyProps(target, mod, "default"), secondTarget && __copyProps(secondTarget, mod, "default"));
+      var __toCommonJS = (mod) => __copyProps(__defProp({}, "__esModule", { value: true }), mod);
+      var src_exports = {};
+      __export(src_exports, {
+        default: () => src_default,
+        foo: () => foo,
+        "bar-baz": () => bar
+      });
+      module.exports = __toCommonJS(src_exports);
+      __reExport(src_exports, require("./qux"), module.exports);
+      const foo = 1, bar = 2;
+      var src_default = foo;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,default,foo,bar-baz");
+    assert_eq!(result.exports[1].provenance, Provenance::EsbuildExport);
+    assert_eq!(result.reexport_specifiers().join(","), "./qux");
+    assert_eq!(result.reexports[0].provenance, Provenance::EsbuildReexport);
+    assert_eq!(result.default_kind, DefaultKind::Object);
+  }
+
+  #[test]
+  fn parse_cjs_exports_esbuild_export_to_exports() {
+    let source = r#"
+      __markAsModule(exports);
+      __export(exports, {
+        foo: () => foo
+      });
+      __reExport(exports, require("./bar"));
+      var other = {};
+      __export(other, {
+        baz: () => baz
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./bar");
+  }
+
+  #[test]
+  fn parse_cjs_exports_tsc5() {
+    let source = r#"
+      "use strict";
+      var __createBinding = (this && this.__createBinding) || (Object.create ? (function(o, m, k, k2) {
+          if (k2 === undefined) k2 = k;
+          var desc = Object.getOwnPropertyDescriptor(m, k);
+          if (!desc || ("get" in desc ? !m.__esModule : desc.writable || desc.configurable)) {
+            desc = { enumerable: true, get: function() { return m[k]; } };
+          }
+          Object.defineProperty(o, k2, desc);
+      }) : (function(o, m, k, k2) {
+          if (k2 === undefined) k2 = k;
+          o[k2] = m[k];
+      }));
+      var __exportStar = (this && this.__exportStar) || function(m, exports) {
+          for (var p in m) if (p !== "default" && !Object.prototype.hasOwnProperty.call(exports, p)) __createBinding(exports, m, p);
+      };
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.c = exports.b = exports.a = void 0;
+      __exportStar(require("./x"), exports);
+      var y_1 = require("./y");
+      Object.defineProperty(exports, "a", { enumerable: true, get: function () { return y_1.a; } });
+      var z_1 = require("./z");
+      Object.defineProperty(exports, "b", { enumerable: true, get: function () { return z_1.default; } });
+      function c() { }
+      exports.c = c;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,c,b,a");
+    assert_eq!(result.reexport_specifiers().join(","), "./x");
+    assert_eq!(result.reexports[0].provenance, Provenance::TslibExportStar);
+    assert_eq!(result.named_reexport_triples().join(","), "a:./y:a,b:./z:default");
+  }
+
+  #[test]
+  fn parse_cjs_exports_tsc_es_module_interop() {
+    let source = r#"
+      "use strict";
+      var __createBinding = (this && this.__createBinding) || (Object.create ? (function(o, m, k, k2) {
+          if (k2 === undefined) k2 = k;
+          var desc = Object.getOwnPropertyDescriptor(m, k);
+          if (!desc || ("get" in desc ? !m.__esModule : desc.writable || desc.configurable)) {
+            desc = { enumerable: true, get: function() { return m[k]; } };
+          }
+          Object.defineProperty(o, k2, desc);
+      }) : (function(o, m, k, k2) {
+          if (k2 === undefined) k2 = k;
+          o[k2] = m[k];
+      }));
+      var __setModuleDefault = (this && this.__setModuleDefault) || (Object.create ? (function(o, v) {
+          Object.defineProperty(o, "default", { enumerable: true, value: v });
+      }) : function(o, v) {
+          o["default"] = v;
+      });
+      var __importStar = (this && this.__importStar) || (function () {
+          var ownKeys = function(o) {
+              ownKeys = Object.getOwnPropertyNames || function (o) {
+                  var ar = [];
+                  for (var k in o) if (Object.prototype.hasOwnProperty.call(o, k)) ar[ar.length] = k;
+                  return ar;
+              };
+              return ownKeys(o);
+          };
+          return function (mod) {
+              if (mod && mod.__esModule) return mod;
+              var result = {};
+              if (mod != null) for (var k = ownKeys(mod), i = 0; i < k.length; i++) if (k[i] !== "default") __createBinding(result, mod, k[i]);
+              __setModuleDefault(result, mod);
+              return result;
+          };
+      })();
+      var __importDefault = (this && this.__importDefault) || function (mod) {
+          return (mod && mod.__esModule) ? mod : { "default": mod };
+      };
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.b = exports.ns = exports.a = void 0;
+      var foo_1 = require("./foo");
+      Object.defineProperty(exports, "a", { enumerable: true, get: function () { return __importDefault(foo_1).default; } });
+      exports.ns = __importStar(require("./bar"));
+      const baz_1 = __importDefault(require("./baz"));
+      Object.defineProperty(exports, "b", { enumerable: true, get: function () { return baz_1.default; } });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,b,ns,a");
+    assert_eq!(
+      result.named_reexport_triples().join(","),
+      "a:./foo:default,ns:./bar:*,b:./baz:default"
+    );
+    assert!(result.warnings.is_empty());
+
+    // importHelpers
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.ns = exports.a = void 0;
+      const tslib_1 = require("tslib");
+      const foo_1 = tslib_1.__importDefault(require("./foo"));
+      Object.defineProperty(exports, "a", { enumerable: true, get: function () { return foo_1.default; } });
+      exports.ns = tslib_1.__importStar(require("./bar"));
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,ns,a");
+    assert_eq!(result.named_reexport_triples().join(","), "a:./foo:default,ns:./bar:*");
+  }
+
+  #[test]
+  fn parse_cjs_exports_tsc_helper_aliases() {
+    let source = r#"
+      "use strict";
+      var o = (this && this.__createBinding) || function(o, m, k, k2) {
+          if (k2 === undefined) k2 = k;
+          o[k2] = m[k];
+      };
+      var s = (this && this.__exportStar) || function(m, e) {
+          for (var p in m) if (p !== "default" && !Object.prototype.hasOwnProperty.call(e, p)) o(e, m, p);
+      };
+      Object.defineProperty(exports, "__esModule", { value: true });
+      exports.foo = void 0;
+      s(require("./a"), exports);
+      const tslib_1 = require("tslib");
+      tslib_1.__exportStar(require("./b"), exports);
+      const { __exportStar: exportStar, __createBinding: createBinding } = require("tslib");
+      (0, exportStar)(require("./c"), exports);
+      var d_1 = require("./d");
+      createBinding(exports, d_1, "bar");
+      o(exports, d_1, "default", "baz");
+      exports.foo = 1;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,foo,bar,baz");
+    assert_eq!(result.reexport_specifiers().join(","), "./a,./b,./c");
+    assert_eq!(result.named_reexport_triples().join(","), "bar:./d:bar,baz:./d:default");
+  }
+
+  #[test]
+  fn parse_cjs_exports_tsc_export_star_loop() {
+    let source = r#"
+      "use strict";
+      function __export(m) {
+          for (var p in m) if (!exports.hasOwnProperty(p)) exports[p] = m[p];
+      }
+      Object.defineProperty(exports, "__esModule", { value: true });
+      __export(require("./a"));
+      var b = require("./b");
+      for (var p in b) if (p !== "default" && !Object.prototype.hasOwnProperty.call(exports, p)) __createBinding(exports, b, p);
+      var c = require("./c");
+      for (var k in c) console.log(k);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule");
+    assert_eq!(result.reexport_specifiers().join(","), "./a,./b");
+  }
+
+  #[test]
+  fn parse_cjs_exports_swc() {
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", {
+          value: true
+      });
+      function _export(target, all) {
+          for(var name in all)Object.defineProperty(target, name, {
+              enumerable: true,
+              get: all[name]
+          });
+      }
+      _export(exports, {
+          default: function() {
+              return _default;
+          },
+          foo: function() {
+              return foo;
+          },
+          bar: function() {
+              return _bar.bar;
+          },
+          baz: function() {
+              return _baz.default;
+          }
+      });
+      _export_star(require("./star"), exports);
+      const _bar = /*#__PURE__*/ _interop_require_wildcard(require("./bar"));
+      const _baz = /*#__PURE__*/ _interop_require_default(require("./baz"));
+      function _export_star(from, to) {
+          Object.keys(from).forEach(function(k) {
+              if (k !== "default" && !Object.prototype.hasOwnProperty.call(to, k)) {
+                  Object.defineProperty(to, k, {
+                      enumerable: true,
+                      get: function() {
+                          return from[k];
+                      }
+                  });
+              }
+          });
+          return from;
+      }
+      function _interop_require_default(obj) {
+          return obj && obj.__esModule ? obj : { default: obj };
+      }
+      const foo = 1;
+      const _default = foo;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,default,foo,bar,baz");
+    assert_eq!(result.exports[1].provenance, Provenance::SwcExport);
+    assert_eq!(result.reexport_specifiers().join(","), "./star");
+    assert_eq!(result.reexports[0].provenance, Provenance::SwcExportStar);
+    assert_eq!(result.named_reexport_triples().join(","), "bar:./bar:bar");
+
+    // the getters of the newer swc versions
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", {
+          value: true
+      });
+      function _export(target, all) {
+          for(var name in all)Object.defineProperty(target, name, {
+              enumerable: true,
+              get: Object.getOwnPropertyDescriptor(all, name).get
+          });
+      }
+      _export(exports, {
+          get a () {
+              return a;
+          },
+          get b () {
+              return _b.b;
+          }
+      });
+      const _b = require("./b");
+      const a = 1;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,a,b");
+    assert_eq!(result.exports[1].provenance, Provenance::SwcExport);
+    assert_eq!(result.named_reexport_triples().join(","), "b:./b:b");
+  }
+
+  #[test]
+  fn parse_cjs_exports_swc_external_helpers() {
+    let source = r#"
+      "use strict";
+      Object.defineProperty(exports, "__esModule", {
+          value: true
+      });
+      Object.defineProperty(exports, "foo", {
+          enumerable: true,
+          get: function() {
+              return _foo.foo;
+          }
+      });
+      const _export_star = require("@swc/helpers/_/_export_star");
+      const _interop_require_wildcard = require("@swc/helpers/_/_interop_require_wildcard");
+      _export_star._(require("./star"), exports);
+      const _foo = /*#__PURE__*/ _interop_require_wildcard._(require("./foo"));
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,foo");
+    assert_eq!(result.reexport_specifiers().join(","), "./star");
+    assert_eq!(result.named_reexports[0].specifier, "./foo");
+    assert_eq!(result.named_reexports[0].member.as_deref(), Some("foo"));
+  }
+
+  #[test]
+  fn parse_cjs_exports_rollup() {
+    let source = r#"
+      'use strict';
+
+      Object.defineProperty(exports, '__esModule', { value: true });
+
+      var dep = require('dep');
+      var other = require('./other.js');
+
+      function _interopNamespaceDefault(e) {
+        var n = Object.create(null);
+        if (e) {
+          Object.keys(e).forEach(function (k) {
+            if (k !== 'default') {
+              var d = Object.getOwnPropertyDescriptor(e, k);
+              Object.defineProperty(n, k, d.get ? d : {
+                enumerable: true,
+                get: function () { return e[k]; }
+              });
+            }
+          });
+        }
+        n.default = e;
+        return Object.freeze(n);
+      }
+
+      var ns__namespace = /*#__PURE__*/_interopNamespaceDefault(require('ns'));
+
+      const foo = 1;
+      function main() {}
+
+      Object.defineProperty(exports, 'bar', {
+        enumerable: true,
+        get: function () { return other.bar; }
+      });
+      exports.default = main;
+      exports.foo = foo;
+      exports.ns = ns__namespace;
+      Object.keys(dep).forEach(function (k) {
+        if (k !== 'default' && !Object.prototype.hasOwnProperty.call(exports, k)) Object.defineProperty(exports, k, {
+          enumerable: true,
+          get: function () { return dep[k]; }
+        });
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,bar,default,foo,ns");
+    assert_eq!(result.reexport_specifiers().join(","), "dep");
+    assert_eq!(result.named_reexport_triples().join(","), "bar:./other.js:bar,ns:ns:*");
+  }
+
+  #[test]
+  fn parse_cjs_exports_parcel() {
+    let source = r#"
+      function $parcel$export(e, n, v, s) {
+        Object.defineProperty(e, n, {get: v, set: s, enumerable: true, configurable: true});
+      }
+      function $parcel$exportWildcard(dest, source) {
+        Object.keys(source).forEach(function(key) {
+          if (key === 'default' || key === '__esModule' || Object.prototype.hasOwnProperty.call(dest, key)) {
+            return;
+          }
+          Object.defineProperty(dest, key, {
+            enumerable: true,
+            get: function get() {
+              return source[key];
+            }
+          });
+        });
+        return dest;
+      }
+      function $parcel$interopDefault(a) {
+        return a && a.__esModule ? a.default : a;
+      }
+      var $4fa36e821943b400$exports = require("dep");
+      var $8d1a$exports = {};
+
+      $parcel$export(module.exports, "foo", () => $8d1a$export$foo);
+      $parcel$export(module.exports, "bar", () => $4fa36e821943b400$exports.bar);
+      $parcel$export(module.exports, "default", () => (/*@__PURE__*/$parcel$interopDefault($4fa36e821943b400$exports)));
+      $parcel$export($8d1a$exports, "baz", () => $8d1a$export$baz);
+      $parcel$exportWildcard(module.exports, $4fa36e821943b400$exports);
+      $parcel$exportWildcard(module.exports, require("./star.js"));
+      const $8d1a$export$foo = 1;
+      const $8d1a$export$baz = 2;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar,default");
+    assert_eq!(result.exports[0].provenance, Provenance::ParcelExport);
+    assert_eq!(result.reexport_specifiers().join(","), "dep,./star.js");
+    assert_eq!(result.reexports[0].provenance, Provenance::ParcelExportWildcard);
+    assert_eq!(result.named_reexports[0].name, "bar");
+    assert_eq!(result.named_reexports[0].member.as_deref(), Some("bar"));
+  }
+
+  #[test]
+  fn parse_cjs_exports_webpack5_library_targets() {
+    let cases = [
+      // commonjs2, minified
+      (
+        r#"
+        (()=>{"use strict";var e={o:(e,t)=>Object.prototype.hasOwnProperty.call(e,t),d:(t,o)=>{for(var r in o)e.o(o,r)&&!e.o(t,r)&&Object.defineProperty(t,r,{enumerable:!0,get:o[r]})},r:e=>{Object.defineProperty(e,"__esModule",{value:!0})}},t={};e.r(t),e.d(t,{bar:()=>r,default:()=>n});const o=1,r=2,n=o;module.exports=t})();
+        "#,
+        "__esModule,bar,default",
+      ),
+      // commonjs2, the runtime helpers are defined after the exports
+      (
+        r#"
+        (()=>{"use strict";var t={};o.d(t,{foo:()=>r});const r=1;module.exports=t;var o={};o.d=(e,t)=>{};})();
+        "#,
+        "foo",
+      ),
+      // commonjs, the exports are copied to `exports`
+      (
+        r#"
+        (()=>{"use strict";var e={d:(t,o)=>{for(var r in o)e.o(o,r)&&!e.o(t,r)&&Object.defineProperty(t,r,{enumerable:!0,get:o[r]})},o:(e,t)=>Object.prototype.hasOwnProperty.call(e,t),r:e=>{Object.defineProperty(e,"__esModule",{value:!0})}},t={};e.r(t),e.d(t,{foo:()=>o});const o=1;var r=exports;for(var n in t)r[n]=t[n];t.__esModule&&Object.defineProperty(r,"__esModule",{value:!0})})();
+        "#,
+        "__esModule,foo",
+      ),
+      // umd2
+      (
+        r#"
+        (function webpackUniversalModuleDefinition(root, factory) {
+        	if(typeof exports === 'object' && typeof module === 'object')
+        		module.exports = factory();
+        	else if(typeof define === 'function' && define.amd)
+        		define([], factory);
+        	else if(typeof exports === 'object')
+        		exports["MyLib"] = factory();
+        	else
+        		root["MyLib"] = factory();
+        })(self, () => {
+        return /******/ (() => { // webpackBootstrap
+        /******/ 	"use strict";
+        /******/ 	var __webpack_require__ = {};
+        /******/ 	(() => {
+        /******/ 		__webpack_require__.d = (exports, definition) => {
+        /******/ 			for(var key in definition) {
+        /******/ 				if(__webpack_require__.o(definition, key) && !__webpack_require__.o(exports, key)) {
+        /******/ 					Object.defineProperty(exports, key, { enumerable: true, get: definition[key] });
+        /******/ 				}
+        /******/ 			}
+        /******/ 		};
+        /******/ 	})();
+        /******/ 	(() => {
+        /******/ 		__webpack_require__.o = (obj, prop) => (Object.prototype.hasOwnProperty.call(obj, prop))
+        /******/ 	})();
+        /******/ 	(() => {
+        /******/ 		__webpack_require__.r = (exports) => {
+        /******/ 			Object.defineProperty(exports, '__esModule', { value: true });
+        /******/ 		};
+        /******/ 	})();
+        var __webpack_exports__ = {};
+        __webpack_require__.r(__webpack_exports__);
+        /* harmony export */ __webpack_require__.d(__webpack_exports__, {
+        /* harmony export */   hello: () => (/* binding */ hello)
+        /* harmony export */ });
+        function hello() {}
+        /******/ 	return __webpack_exports__;
+        /******/ })()
+        ;
+        });
+        "#,
+        "__esModule,hello",
+      ),
+      // rspack
+      (
+        r#"
+        "use strict";
+        var __webpack_require__ = {};
+        // webpack/runtime/define_property_getters
+        (() => {
+        __webpack_require__.d = function(exports, definition) {
+        	for(var key in definition) {
+                if(__webpack_require__.o(definition, key) && !__webpack_require__.o(exports, key)) {
+                    Object.defineProperty(exports, key, { enumerable: true, get: definition[key] });
+                }
+            }
+        };
+        })();
+        // webpack/runtime/has_own_property
+        (() => {
+        __webpack_require__.o = function (obj, prop) {
+            return Object.prototype.hasOwnProperty.call(obj, prop);
+        };
+        })();
+        // webpack/runtime/make_namespace_object
+        (() => {
+        __webpack_require__.r = function(exports) {
+        	Object.defineProperty(exports, '__esModule', { value: true });
+        };
+        })();
+        var __webpack_exports__ = {};
+        __webpack_require__.r(__webpack_exports__);
+        __webpack_require__.d(__webpack_exports__, {
+          add: function() { return add; },
+          VERSION: function() { return VERSION; }
+        });
+        function add(a, b) { return a + b; }
+        const VERSION = "1.0.0";
+        var __webpack_export_target__ = exports;
+        for(var __webpack_i__ in __webpack_exports__) __webpack_export_target__[__webpack_i__] = __webpack_exports__[__webpack_i__];
+        if(__webpack_exports__.__esModule) Object.defineProperty(__webpack_export_target__, '__esModule', { value: true });
+        "#,
+        "__esModule,add,VERSION",
+      ),
+    ];
+    for (source, expected) in cases {
+      let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+      let result = lexer.analyze("production", false);
+      assert_eq!(result.export_names().join(","), expected, "{}", source);
+    }
+
+    // the `r`/`d` calls of an object that is not the webpack runtime
+    let source = r#"
+      var x = { r() {}, d() {} };
+      x.r(exports);
+      x.d(exports, "foo", function () {});
+      lib.d(exports, { bar: () => bar });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert!(result.exports.is_empty());
+
+    // `d`/`r` properties that are assigned data, not functions
+    let source = r#"
+      var cfg = {};
+      cfg.d = 1;
+      cfg.r = "r";
+      cfg.d(exports, { foo: () => foo });
+      cfg.r(exports);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert!(result.exports.is_empty());
+  }
+
+  #[test]
+  fn parse_cjs_exports_amd() {
+    let source = r#"
+      define(["require", "exports", "./dep", "lib"], function (require, exports, dep_1, lib) {
+        "use strict";
+        Object.defineProperty(exports, "__esModule", { value: true });
+        exports.bar = exports.foo = void 0;
+        exports.foo = 1;
+        exports.bar = dep_1.bar;
+        __exportStar(lib, exports);
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::Amd);
+    assert_eq!(result.export_names().join(","), "__esModule,bar,foo");
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
+    assert_eq!(result.named_reexports[0].name, "bar");
+
+    let source = r#"
+      define("my-module", function (require, exports, module) {
+        var dep = require("./dep");
+        module.exports = { foo: dep.foo, bar: 1 };
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+
+    let source = r#"
+      define(["jquery"], function ($) {
+        function foo() {}
+        return { foo: foo, version: "1.0.0" };
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,version");
+
+    let source = r#"
+      define(["./base"], (base) => base);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.reexport_specifiers().join(","), "./base");
+
+    let lexer =
+      CommonJSModuleLexer::init("index.js", r#"define({ foo: 1, bar: 2 });"#).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+
+    let source = r#"
+      function define(fn) { return fn(); }
+      define(function () { return { foo: 1 }; });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::Unknown);
+    assert!(result.exports.is_empty());
+  }
+
+  #[test]
+  fn parse_cjs_exports_system() {
+    let source = r#"
+      System.register(["./dep", "lib"], function (_export, _context) {
+        "use strict";
+        var bar;
+        function foo() {}
+        _export("foo", foo);
+        return {
+          setters: [function (_dep) {
+            bar = _dep.bar;
+            _export("baz", _dep.baz);
+          }, function (_lib) {
+            var _exportObj = {};
+            for (var _key in _lib) {
+              if (_key !== "default" && _key !== "__esModule") _exportObj[_key] = _lib[_key];
+            }
+            _export(_exportObj);
+          }],
+          execute: function () {
+            _export("bar", bar);
+            _export({ qux: 1, default: 2 });
+          }
+        };
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.js", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.format, ModuleFormat::System);
+    assert_eq!(result.export_names().join(","), "foo,baz,bar,qux,default");
+    assert_eq!(result.exports[0].provenance, Provenance::SystemExport);
+    assert_eq!(result.reexport_specifiers().join(","), "lib");
+    assert_eq!(result.named_reexports[0].name, "baz");
+  }
+
+  #[test]
+  fn parse_cjs_exports_define_properties() {
+    let source = r#"
+      var desc = { enumerable: true, value: 3 };
+      Object.defineProperties(exports, {
+        __esModule: { value: true },
+        foo: { enumerable: true, get: function () { return foo; } },
+        bar: { enumerable: true, get() { return require("./bar").bar; } },
+        baz: desc,
+        qux: { enumerable: false },
+      });
+      Reflect.defineProperty(exports, "quux", { value: 4 });
+      Object.setPrototypeOf(module.exports, require("./base"));
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "__esModule,foo,bar,baz,quux");
+    assert_eq!(result.exports[0].provenance, Provenance::DefineProperties);
+    assert_eq!(result.exports[4].provenance, Provenance::DefineProperty);
+    assert_eq!(result.named_reexports[0].name, "bar");
+    assert_eq!(result.reexport_specifiers().join(","), "./base");
+    assert_eq!(result.reexports[0].provenance, Provenance::SetPrototypeOf);
+
+    let source = r#"
+      Object.defineProperties(module, { exports: { value: { foo: 1, bar: 2 } } });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo,bar");
+
+    // only the `exports` property of `module` is the module exports
+    let source = r#"
+      var e = exports;
+      e.foo = 1;
+      Object.defineProperties(module, { e: { value: { bar: 2 } } });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "foo");
+
+    let lexer = CommonJSModuleLexer::init("index.cjs", "Object.defineProperties(exports, getDescriptors());")
+      .expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert!(result.exports.is_empty());
+    assert_eq!(result.warnings[0].kind, WarningKind::DynamicDefineProperty);
+  }
+
+  #[test]
+  fn parse_cjs_exports_const_loops() {
+    let source = r#"
+      var methods = ["delete", "head", "options"];
+      function make(method) { return function (url) { return request(method, url); }; }
+      ["get", "post", "put"].forEach(m => exports[m] = make(m));
+      methods.forEach(function (method) {
+        exports[method] = make(method);
+      });
+      for (const name of ["isArray", "isObject"]) {
+        exports[name] = require("./is")[name];
+      }
+      var aliases = { each: "forEach", first: "head" };
+      for (var key in aliases) {
+        if (aliases.hasOwnProperty(key)) exports[key] = exports[aliases[key]];
+      }
+      Object.keys({ trim: 1 }).forEach(function (key) {
+        Object.defineProperty(exports, key, { enumerable: true, get: function () { return 1; } });
+      });
+      [unknown, "patch"].forEach(m => { exports[m] = make(m); });
+      for (const m of getNames()) exports[m] = make(m);
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(
+      result.export_names().join(","),
+      "get,post,put,delete,head,options,isArray,isObject,each,first,trim"
+    );
+    assert_eq!(result.exports[0].provenance, Provenance::LoopAssignment);
+    // the list with a non-constant element is incomplete, so it's not unrolled
+    assert_eq!(
+      result.warnings.iter().map(|w| w.kind).collect::<Vec<_>>(),
+      vec![WarningKind::DynamicAssignment, WarningKind::DynamicAssignment]
+    );
+
+    // the elements of a mutated array are unknown
+    let source = r#"
+      var names = ["foo"];
+      names.push(extra);
+      names.forEach(function (name) {
+        exports[name] = 1;
+      });
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert!(result.exports.is_empty());
+    assert_eq!(result.warnings[0].kind, WarningKind::DynamicAssignment);
+
+    let source = r#"
+      var api = {};
+      ["get", "post"].forEach(function (method) {
+        api[method] = function (url) { return request(method, url); };
+      });
+      module.exports = api;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "get,post");
+
+    let source = r#"
+      function axios() {}
+      for (const m of ["all", "spread"]) axios[m] = m;
+      module.exports = axios;
+    "#;
+    let lexer = CommonJSModuleLexer::init("index.cjs", source).expect("could not parse the module");
+    let result = lexer.analyze("production", false);
+    assert_eq!(result.export_names().join(","), "all,spread");
   }
 }
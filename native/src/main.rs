@@ -7,7 +7,7 @@ use std::{env, fs};
 
 fn main() {
   let mut stdout = stdout();
-  let specifier = env::args().skip(1).next().expect("missing specifier argument");
+  let specifier = env::args().nth(1).expect("missing specifier argument");
   let node_env = env::var("NODE_ENV").unwrap_or("production".to_owned());
   let wd = env::current_dir()
     .expect("failed to get current working directory")
@@ -24,16 +24,16 @@ fn main() {
   } else {
     resolve(&wd, &specifier, None).expect("failed to resolve specifier")
   };
-  let mut requires = vec![(js_filename, false)];
+  // the modules to walk, with the member of the module that is reexported, e.g. `require("./lib").default`
+  let mut requires: Vec<(String, bool, Option<String>)> = vec![(js_filename, false, None)];
   let mut named_exports = IndexSet::new();
-  while requires.len() > 0 {
-    let (js_filename, call_mode) = requires.pop().unwrap();
-    let code = fs::read_to_string(&js_filename).expect(("failed to read ".to_owned() + js_filename.as_str()).as_str());
+  while let Some((js_filename, call_mode, member)) = requires.pop() {
+    let code = fs::read_to_string(&js_filename).unwrap_or_else(|_| panic!("failed to read {}", js_filename));
     if js_filename.ends_with(".json") {
       let value: serde_json::Value = serde_json::from_str(&code).unwrap();
       if let Some(value) = value.as_object() {
         for key in value.keys() {
-          if is_js_identifier(&key) {
+          if is_js_identifier(key) {
             named_exports.insert(key.clone());
           }
         }
@@ -41,35 +41,44 @@ fn main() {
       continue;
     }
     let lexer = CommonJSModuleLexer::init(&js_filename, &code).expect("failed to parse module");
-    let (exports, reexports) = lexer.analyze(&node_env, call_mode);
-    if exports.len() == 0 && reexports.len() == 1 && named_exports.len() == 0 {
-      let reexport = reexports[0].clone();
-      if !reexport.starts_with(".")
-        && !reexport.starts_with("/")
-        && !reexport.ends_with("()")
-        && !is_node_builtin_module(&reexport)
+    let result = lexer.analyze(&node_env, call_mode);
+    if let Some(member) = member {
+      // the exports of the member are only known if it's a reexport of another module
+      if let Some(reexport) = result.named_reexports.iter().find(|r| r.name == member) {
+        if !is_node_builtin_module(&reexport.specifier) {
+          requires.push((
+            resolve(&wd, &reexport.specifier, Some(js_filename.clone())).expect("failed to resolve reexport"),
+            call_mode,
+            reexport.member.clone(),
+          ));
+        }
+      }
+      continue;
+    }
+    if result.exports.is_empty() && result.reexports.len() == 1 && named_exports.is_empty() {
+      let reexport = &result.reexports[0];
+      let specifier = &reexport.specifier;
+      if !specifier.starts_with(".")
+        && !specifier.starts_with("/")
+        && !reexport.called
+        && reexport.member.is_none()
+        && !is_node_builtin_module(specifier)
       {
         stdout
-          .write_all(("@".to_owned() + reexport.as_str() + "\n").as_bytes())
+          .write_all(("@".to_owned() + specifier.as_str() + "\n").as_bytes())
           .expect("failed to write result to stdout");
         return;
       }
     }
-    for export in exports {
-      named_exports.insert(export);
+    for export in result.exports {
+      named_exports.insert(export.name);
     }
-    for reexport in reexports {
-      let mut call_mode = false;
-      let reexport = if reexport.ends_with("()") {
-        call_mode = true;
-        reexport[..reexport.len() - 2].to_owned()
-      } else {
-        reexport
-      };
-      if !is_node_builtin_module(&reexport) {
+    for reexport in result.reexports {
+      if !is_node_builtin_module(&reexport.specifier) {
         requires.push((
-          resolve(&wd, &reexport, Some(js_filename.clone())).expect("failed to resolve reexport"),
-          call_mode,
+          resolve(&wd, &reexport.specifier, Some(js_filename.clone())).expect("failed to resolve reexport"),
+          reexport.called,
+          reexport.member,
         ));
       }
     }
@@ -170,7 +179,7 @@ fn file_exists(path: &str) -> io::Result<bool> {
 }
 
 fn is_js_identifier(s: &str) -> bool {
-  if s.len() == 0 {
+  if s.is_empty() {
     return false;
   }
   let mut chars = s.chars();
@@ -183,21 +192,15 @@ fn is_js_identifier(s: &str) -> bool {
       return false;
     }
   }
-  return true;
+  true
 }
 
 fn is_alphabetic(c: char) -> bool {
-  match c {
-    'a'..='z' | 'A'..='Z' | '_' | '$' => true,
-    _ => false,
-  }
+  matches!(c, 'a'..='z' | 'A'..='Z' | '_' | '$')
 }
 
 fn is_numberic(c: char) -> bool {
-  match c {
-    '0'..='9' => true,
-    _ => false,
-  }
+  c.is_ascii_digit()
 }
 
 fn is_node_builtin_module(specifier: &str) -> bool {
@@ -1,4 +1,4 @@
-import { initSync, parse as __wbg_parse } from "./pkg/cjs-module-lexer.js";
+import { initSync, parse as __wbg_parse, parseAll as __wbg_parseAll } from "./pkg/cjs-module-lexer.js";
 
 let wasm;
 const wasmPath = "./pkg/cjs-module-lexer_bg.wasm";
@@ -24,13 +24,38 @@ if (globalThis.process || globalThis.Deno || globalThis.Bun) {
 
 initSync({ module: wasm });
 
+/**
+ * @typedef {{ start: number, end: number, line: number, column: number }} Span
+ * @typedef {"object" | "function" | "class" | "primitive" | "reexport" | "unknown"} DefaultKind what `module.exports` is bound to
+ * @typedef {string} Provenance the recognized pattern that produced the export, e.g. "defineProperty"
+ * @typedef {{ name: string, provenance: Provenance, span: Span }} Export
+ * @typedef {{ specifier: string, called: boolean, member?: string, extends: string[], provenance: Provenance, span: Span }} Reexport
+ * @typedef {{ name: string, specifier: string, member?: string, provenance: Provenance, span: Span }} NamedReexport
+ * @typedef {{ specifier: string, dynamic: boolean, conditional: boolean, inTry: boolean, span: Span }} Require
+ * @typedef {{ kind: "dynamicAssignment" | "dynamicDefineProperty" | "unknownModuleExports" | "invalidDefine", message: string, span: Span }} Warning
+ * @typedef {{ severity: "error" | "warning" | "note", message: string, code?: string, file: string, line: number, column: number, span?: Span }} Diagnostic
+ * @typedef {{ nodeEnv?: 'development' | 'production', callMode?: boolean, defines?: Record<string, string>, platform?: 'node' | 'browser' | 'worker' | 'deno', recover?: boolean }} ParseOptions
+ * @typedef {{ exports: Export[], reexports: Reexport[], namedReexports: NamedReexport[], defaultKind: DefaultKind, callable: boolean, constructible: boolean, esModule: boolean, warnings: Warning[], format: "cjs" | "esm" | "mixed" | "amd" | "system" | "unknown", esmExports: string[], esmReexports: string[], requires: Require[], errors: Diagnostic[] }} ParseResult
+ */
+
 /**
  * parse the given cjs module and return the name exports and reexports
  * @param {string} filename
  * @param {string} code
- * @param {{ nodeEnv?: 'development' | 'production', callMode?: boolean }} options
- * @returns {{ exports: string[], reexports: string[] }}
+ * @param {ParseOptions} options
+ * @returns {ParseResult}
  */
 export function parse(filename, code, options = {}) {
   return __wbg_parse(filename, code, options);
 }
+
+/**
+ * parse the given cjs module with each of the given options and merge the results
+ * @param {string} filename
+ * @param {string} code
+ * @param {ParseOptions[]} options
+ * @returns {{ results: ParseResult[], exports: { name: string, foundIn: number[], partial: boolean }[], reexports: { specifier: string, called: boolean, member?: string, foundIn: number[], partial: boolean }[], namedReexports: { name: string, specifier: string, member?: string, foundIn: number[], partial: boolean }[] }}
+ */
+export function parseAll(filename, code, options) {
+  return __wbg_parseAll(filename, code, options);
+}
@@ -1,39 +1,138 @@
-use lexer::CommonJSModuleLexer;
+use lexer::{
+  AnalyzeOptions, AnalyzeResult, CommonJSModuleLexer, DefaultKind, Diagnostic, DiagnosticBuffer, Export, MergedExport,
+  MergedNamedReexport, MergedReexport, ModuleFormat, NamedReexport, Platform, Reexport, Require, Warning,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Options {
   node_env: Option<String>,
   call_mode: Option<bool>,
+  defines: Option<HashMap<String, String>>,
+  platform: Option<Platform>,
+  recover: Option<bool>,
+}
+
+impl From<Options> for AnalyzeOptions {
+  fn from(options: Options) -> Self {
+    AnalyzeOptions {
+      node_env: options.node_env.unwrap_or_else(|| "production".to_owned()),
+      call_mode: options.call_mode.unwrap_or_default(),
+      defines: options.defines.unwrap_or_default(),
+      platform: options.platform,
+    }
+  }
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Output {
-  pub exports: Vec<String>,
-  pub reexports: Vec<String>,
+  pub exports: Vec<Export>,
+  pub reexports: Vec<Reexport>,
+  pub named_reexports: Vec<NamedReexport>,
+  pub default_kind: DefaultKind,
+  pub callable: bool,
+  pub constructible: bool,
+  pub es_module: bool,
+  pub warnings: Vec<Warning>,
+  pub format: ModuleFormat,
+  pub esm_exports: Vec<String>,
+  pub esm_reexports: Vec<String>,
+  pub requires: Vec<Require>,
+  pub errors: Vec<Diagnostic>,
+}
+
+impl From<AnalyzeResult> for Output {
+  fn from(result: AnalyzeResult) -> Self {
+    Output {
+      exports: result.exports,
+      reexports: result.reexports,
+      named_reexports: result.named_reexports,
+      default_kind: result.default_kind,
+      callable: result.callable,
+      constructible: result.constructible,
+      es_module: result.es_module,
+      warnings: result.warnings,
+      format: result.format,
+      esm_exports: result.esm_exports,
+      esm_reexports: result.esm_reexports,
+      requires: result.requires,
+      errors: vec![],
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedOutput {
+  pub results: Vec<Output>,
+  pub exports: Vec<MergedExport>,
+  pub reexports: Vec<MergedReexport>,
+  pub named_reexports: Vec<MergedNamedReexport>,
 }
 
 #[wasm_bindgen(js_name = "parse")]
 pub fn parse(filename: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
-  let options: Options = serde_wasm_bindgen::from_value(options).unwrap_or(Options {
-    node_env: None,
-    call_mode: None,
-  });
-  let lexer = match CommonJSModuleLexer::init(filename, code) {
-    Ok(lexer) => lexer,
-    Err(e) => {
-      return Err(JsError::new(&e.to_string()).into());
-    }
-  };
-  let node_env = if let Some(env) = options.node_env {
-    env
+  // the options are optional, but invalid options are reported instead of being ignored
+  let options: Options = if options.is_undefined() || options.is_null() {
+    Options::default()
   } else {
-    "production".to_owned()
+    serde_wasm_bindgen::from_value(options)?
+  };
+  let (lexer, errors) = init_lexer(filename, code, options.recover.unwrap_or_default())?;
+  let result = lexer.analyze_with_options(&options.into());
+  let output = Output {
+    errors,
+    ..Output::from(result)
   };
-  let call_mode = if let Some(ok) = options.call_mode { ok } else { false };
-  let (exports, reexports) = lexer.analyze(&node_env, call_mode);
-  Ok(serde_wasm_bindgen::to_value(&Output { exports, reexports }).unwrap())
+  Ok(serde_wasm_bindgen::to_value(&output).unwrap())
+}
+
+#[wasm_bindgen(js_name = "parseAll")]
+pub fn parse_all(filename: &str, code: &str, options: JsValue) -> Result<JsValue, JsValue> {
+  let options: Vec<Options> = serde_wasm_bindgen::from_value(options)?;
+  // the module is parsed only once, so it recovers from syntax errors if any of the options asks for it
+  let recover = options.iter().any(|options| options.recover.unwrap_or_default());
+  let (lexer, errors) = init_lexer(filename, code, recover)?;
+  let options = options.into_iter().map(AnalyzeOptions::from).collect::<Vec<_>>();
+  let merged = lexer.analyze_all(&options);
+  Ok(
+    serde_wasm_bindgen::to_value(&MergedOutput {
+      results: merged
+        .results
+        .into_iter()
+        .map(|result| Output {
+          errors: errors.clone(),
+          ..Output::from(result)
+        })
+        .collect(),
+      exports: merged.exports,
+      reexports: merged.reexports,
+      named_reexports: merged.named_reexports,
+    })
+    .unwrap(),
+  )
+}
+
+// parse the module, the syntax errors are returned instead of thrown with `recover`
+fn init_lexer(filename: &str, code: &str, recover: bool) -> Result<(CommonJSModuleLexer, Vec<Diagnostic>), JsValue> {
+  if recover {
+    let (lexer, diagnostics) = CommonJSModuleLexer::init_recoverable(filename, code);
+    return Ok((lexer, diagnostics.into_diagnostics()));
+  }
+  match CommonJSModuleLexer::init(filename, code) {
+    Ok(lexer) => Ok((lexer, vec![])),
+    Err(e) => Err(to_js_error(e)),
+  }
+}
+
+// create a JS error with the message of the diagnostics, the structured diagnostics are set to its `diagnostics` property
+fn to_js_error(diagnostics: DiagnosticBuffer) -> JsValue {
+  let error = js_sys::Error::new(&diagnostics.to_string());
+  let diagnostics = serde_wasm_bindgen::to_value(diagnostics.diagnostics()).unwrap();
+  js_sys::Reflect::set(&error, &JsValue::from_str("diagnostics"), &diagnostics).unwrap();
+  error.into()
 }
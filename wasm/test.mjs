@@ -1,6 +1,6 @@
 import { parse } from "./index.mjs";
 
-const { exports, reexports } = parse("test.cjs", `
+const result = parse("test.cjs", `
   module.exports = require("./lib");
   exports.a = "a";
   module.exports.b = "b";
@@ -9,10 +9,15 @@ const { exports, reexports } = parse("test.cjs", `
   const key = "foo"
   Object.defineProperty(exports, key, { value: "e" });
 `);
+const exports = result.exports.map((e) => e.name);
+const reexports = result.reexports.map((r) => r.specifier);
 if (exports.join(",") !== "a,b,c,__esModule,foo") {
   throw new Error("exports is expected to be a,b,c,__esModule,foo, but got " + exports.join(","));
 }
 if (reexports.join(",") !== "./lib") {
   throw new Error("reexports is expected to be ./lib, but got " + reexports.join(","));
 }
+if (result.exports[0].span.line !== 3) {
+  throw new Error("the span of export 'a' is expected to be at line 3, but got " + result.exports[0].span.line);
+}
 console.log("✅ test passed");